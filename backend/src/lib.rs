@@ -0,0 +1,286 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use cotw::{Board, Game, GameHistory, LegalMove, Move, Outcome, Player};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::broadcast;
+
+pub mod ws;
+
+pub type GameId = u64;
+
+struct GameEntry {
+    history: GameHistory,
+    updates: broadcast::Sender<GameState>,
+    /// The tokens given to whoever joined as beige and black.
+    seats: [Option<String>; 2],
+}
+
+fn seat(player: Player) -> usize {
+    match player {
+        Player::Beige => 0,
+        Player::Black => 1,
+    }
+}
+
+/// A token nobody can guess: 128 bits from the operating system's random number generator.
+fn new_token() -> String {
+    let mut bytes = [0; 16];
+    getrandom::fill(&mut bytes).expect("the system has a random number generator");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[derive(Default)]
+pub struct Games {
+    games: Mutex<HashMap<GameId, GameEntry>>,
+    next_id: AtomicU64,
+}
+
+impl Games {
+    pub fn create(&self, game: Game) -> (GameId, GameState) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let history = GameHistory::new(game);
+        let state = GameState::new(id, &history);
+        let (updates, _) = broadcast::channel(16);
+        self.games.lock().unwrap().insert(
+            id,
+            GameEntry {
+                history,
+                updates,
+                seats: [None, None],
+            },
+        );
+        (id, state)
+    }
+
+    pub fn state(&self, id: GameId) -> Result<GameState, ApiError> {
+        let games = self.games.lock().unwrap();
+        let entry = games.get(&id).ok_or(ApiError::NoSuchGame(id))?;
+        Ok(GameState::new(id, &entry.history))
+    }
+
+    /// The current state of the game, and a receiver for the state after every move from now on.
+    pub fn subscribe(
+        &self,
+        id: GameId,
+    ) -> Result<(GameState, broadcast::Receiver<GameState>), ApiError> {
+        let games = self.games.lock().unwrap();
+        let entry = games.get(&id).ok_or(ApiError::NoSuchGame(id))?;
+        Ok((
+            GameState::new(id, &entry.history),
+            entry.updates.subscribe(),
+        ))
+    }
+
+    /// Take a seat in the game, returning the token needed to move as that player.
+    pub fn join(&self, id: GameId, player: Player) -> Result<String, ApiError> {
+        let mut games = self.games.lock().unwrap();
+        let entry = games.get_mut(&id).ok_or(ApiError::NoSuchGame(id))?;
+        let seat = &mut entry.seats[seat(player)];
+        if seat.is_some() {
+            return Err(ApiError::SeatTaken(player));
+        }
+        Ok(seat.insert(new_token()).clone())
+    }
+
+    /// The player who was given `token` when they joined.
+    pub fn player(&self, id: GameId, token: &str) -> Result<Player, ApiError> {
+        let games = self.games.lock().unwrap();
+        let entry = games.get(&id).ok_or(ApiError::NoSuchGame(id))?;
+        [Player::Beige, Player::Black]
+            .into_iter()
+            .find(|&player| entry.seats[seat(player)].as_deref() == Some(token))
+            .ok_or(ApiError::InvalidToken)
+    }
+
+    /// Make a move for `player`, who must be the player the move is for.
+    pub fn make_move(
+        &self,
+        id: GameId,
+        player: Player,
+        move_: Move,
+    ) -> Result<GameState, ApiError> {
+        let mut games = self.games.lock().unwrap();
+        let entry = games.get_mut(&id).ok_or(ApiError::NoSuchGame(id))?;
+        if move_.player != player {
+            return Err(ApiError::NotPlaying(move_.player));
+        }
+        let move_ = LegalMove::from_move(entry.history.game(), move_)?;
+        entry.history.make_move(&move_)?;
+        let state = GameState::new(id, &entry.history);
+        // nobody may be subscribed
+        let _ = entry.updates.send(state.clone());
+        Ok(state)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub id: GameId,
+    pub position: String,
+    pub to_move: Player,
+    pub board: Board,
+    pub outcome: Option<Outcome>,
+    /// The moves played so far, in move notation.
+    pub moves: Vec<String>,
+    pub legal_moves: Vec<Move>,
+}
+
+impl GameState {
+    pub fn new(id: GameId, history: &GameHistory) -> GameState {
+        let game = history.game();
+        GameState {
+            id,
+            position: game.to_position_string(),
+            to_move: game.to_move(),
+            board: *game.board(),
+            outcome: game.outcome(),
+            moves: history.moves().map(|move_| move_.to_string()).collect(),
+            legal_moves: game
+                .legal_moves()
+                .into_iter()
+                .map(LegalMove::to_move)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    NoSuchGame(GameId),
+    InvalidPosition(cotw::PositionError),
+    IllegalMove(cotw::MoveError),
+    NotPlaying(Player),
+    SeatTaken(Player),
+    InvalidToken,
+}
+
+impl From<cotw::MoveError> for ApiError {
+    fn from(err: cotw::MoveError) -> Self {
+        ApiError::IllegalMove(err)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::NoSuchGame(id) => write!(f, "no game with id {}", id),
+            ApiError::InvalidPosition(err) => err.fmt(f),
+            ApiError::IllegalMove(err) => err.fmt(f),
+            ApiError::NotPlaying(player) => write!(f, "not playing as {:?}", player),
+            ApiError::SeatTaken(player) => write!(f, "someone is already playing as {:?}", player),
+            ApiError::InvalidToken => write!(f, "missing or unknown player token"),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiError::NoSuchGame(_) => StatusCode::NOT_FOUND,
+            ApiError::InvalidPosition(_) => StatusCode::BAD_REQUEST,
+            ApiError::IllegalMove(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::NotPlaying(_) => StatusCode::FORBIDDEN,
+            ApiError::SeatTaken(_) => StatusCode::CONFLICT,
+            ApiError::InvalidToken => StatusCode::UNAUTHORIZED,
+        };
+        let message = self.to_string();
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateGame {
+    /// A position string to start from instead of the starting position.
+    pub position: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JoinGame {
+    pub player: Player,
+}
+
+/// Sent back after joining. Moves are made by sending the token as `Authorization: Bearer <token>`,
+/// or over the WebSocket by connecting with `?token=<token>`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Seat {
+    pub player: Player,
+    pub token: String,
+}
+
+pub fn app(games: Arc<Games>) -> Router {
+    Router::new()
+        .route("/games", post(create_game))
+        .route("/games/{id}", get(game_state))
+        .route("/games/{id}/players", post(join_game))
+        .route("/games/{id}/moves", get(legal_moves).post(make_move))
+        .route("/games/{id}/ws", get(ws::subscribe))
+        .with_state(games)
+}
+
+async fn create_game(
+    State(games): State<Arc<Games>>,
+    body: Option<Json<CreateGame>>,
+) -> Result<(StatusCode, Json<GameState>), ApiError> {
+    let game = match body.and_then(|Json(body)| body.position) {
+        Some(position) => {
+            Game::from_position_string(&position).map_err(ApiError::InvalidPosition)?
+        }
+        None => Game::default(),
+    };
+    let (_, state) = games.create(game);
+    Ok((StatusCode::CREATED, Json(state)))
+}
+
+async fn game_state(
+    State(games): State<Arc<Games>>,
+    Path(id): Path<GameId>,
+) -> Result<Json<GameState>, ApiError> {
+    Ok(Json(games.state(id)?))
+}
+
+async fn legal_moves(
+    State(games): State<Arc<Games>>,
+    Path(id): Path<GameId>,
+) -> Result<Json<Vec<Move>>, ApiError> {
+    Ok(Json(games.state(id)?.legal_moves))
+}
+
+async fn join_game(
+    State(games): State<Arc<Games>>,
+    Path(id): Path<GameId>,
+    Json(JoinGame { player }): Json<JoinGame>,
+) -> Result<(StatusCode, Json<Seat>), ApiError> {
+    let token = games.join(id, player)?;
+    Ok((StatusCode::CREATED, Json(Seat { player, token })))
+}
+
+async fn make_move(
+    State(games): State<Arc<Games>>,
+    Path(id): Path<GameId>,
+    headers: HeaderMap,
+    Json(move_): Json<Move>,
+) -> Result<Json<GameState>, ApiError> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(ApiError::InvalidToken)?;
+    let player = games.player(id, token)?;
+    Ok(Json(games.make_move(id, player, move_)?))
+}
+
+pub async fn serve(listener: tokio::net::TcpListener, games: Arc<Games>) -> std::io::Result<()> {
+    axum::serve(listener, app(games)).await
+}
@@ -1,3 +1,14 @@
-fn main() {
-    println!("Hello, world!");
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("127.0.0.1:3000"));
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!("listening on {}", listener.local_addr()?);
+
+    backend::serve(listener, Arc::new(backend::Games::default())).await
 }
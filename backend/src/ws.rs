@@ -0,0 +1,115 @@
+//! Live updates over a WebSocket at `/games/{id}/ws`. Players connect with `?token=<token>`, using
+//! the token they were given when they joined, and anyone else connects as a spectator. Every
+//! client is sent a [`ServerMessage::State`] when it connects and after every move made in the
+//! game, however the move was submitted. Players submit moves with a [`ClientMessage::Move`], and
+//! are sent a [`ServerMessage::Error`] if it is rejected. Reconnecting sends the current state
+//! again.
+
+use crate::{ApiError, GameId, GameState, Games};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    response::Response,
+};
+use cotw::{Move, Player};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Move {
+        #[serde(rename = "move")]
+        move_: Move,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    State(GameState),
+    Error { error: String },
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Subscription {
+    pub token: Option<String>,
+}
+
+pub(crate) async fn subscribe(
+    State(games): State<Arc<Games>>,
+    Path(id): Path<GameId>,
+    Query(subscription): Query<Subscription>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    // fail before upgrading if the game doesn't exist or the token is wrong
+    games.state(id)?;
+    let player = match subscription.token {
+        Some(token) => Some(games.player(id, &token)?),
+        None => None,
+    };
+    Ok(upgrade.on_upgrade(move |socket| connection(socket, games, id, player)))
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("game state is serializable");
+    socket.send(Message::Text(text.into())).await
+}
+
+async fn connection(mut socket: WebSocket, games: Arc<Games>, id: GameId, player: Option<Player>) {
+    let Ok((state, mut updates)) = games.subscribe(id) else {
+        return;
+    };
+    if send(&mut socket, &ServerMessage::State(state))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    match handle(&games, id, player, &text) {
+                        // the new state arrives through `updates`
+                        Ok(()) => continue,
+                        Err(error) => ServerMessage::Error { error },
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+
+            update = updates.recv() => match update {
+                Ok(state) => ServerMessage::State(state),
+                // we missed some moves, so just send where the game is now
+                Err(RecvError::Lagged(_)) => match games.state(id) {
+                    Ok(state) => ServerMessage::State(state),
+                    Err(_) => return,
+                },
+                Err(RecvError::Closed) => return,
+            },
+        };
+
+        if send(&mut socket, &reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn handle(games: &Games, id: GameId, player: Option<Player>, text: &str) -> Result<(), String> {
+    let message = serde_json::from_str::<ClientMessage>(text).map_err(|err| err.to_string())?;
+    match message {
+        ClientMessage::Move { move_ } => {
+            let player = player.ok_or(ApiError::NotPlaying(move_.player));
+            player
+                .and_then(|player| games.make_move(id, player, move_))
+                .map_err(|err| err.to_string())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use backend::{GameState, Games, Seat};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::sync::Arc;
+
+async fn join(client: &reqwest::Client, url: &str, id: u64, player: &str) -> reqwest::Response {
+    client
+        .post(format!("{url}/games/{id}/players"))
+        .json(&json!({ "player": player }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn start() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(backend::serve(listener, Arc::new(Games::default())));
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn play_moves() {
+    let url = start().await;
+    let client = reqwest::Client::new();
+
+    let response = client.post(format!("{url}/games")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let state: GameState = response.json().await.unwrap();
+    assert_eq!(state.position, "3C3/2MMM2/3M3/7/3m3/2mmm2/3c3 beige");
+    assert!(!state.legal_moves.is_empty());
+
+    let moves: Vec<serde_json::Value> = client
+        .get(format!("{url}/games/{}/moves", state.id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(moves.len(), state.legal_moves.len());
+
+    let response = join(&client, &url, state.id, "Beige").await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let beige: Seat = response.json().await.unwrap();
+    let black: Seat = join(&client, &url, state.id, "Black")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_ne!(beige.token, black.token);
+    let response = join(&client, &url, state.id, "Black").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let move_ = json!({
+        "player": "Beige",
+        "messenger": "d5",
+        "direction": "S",
+        "first_throw": "N",
+        "extra_throws": ["NE"],
+    });
+    // moving needs the token for the player being moved
+    let response = client
+        .post(format!("{url}/games/{}/moves", state.id))
+        .json(&move_)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = client
+        .post(format!("{url}/games/{}/moves", state.id))
+        .bearer_auth(&black.token)
+        .json(&move_)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = client
+        .post(format!("{url}/games/{}/moves", state.id))
+        .bearer_auth(&beige.token)
+        .json(&move_)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let state: GameState = response.json().await.unwrap();
+    assert_eq!(state.moves, ["d5-S/N,NE"]);
+    assert_eq!(state.to_move, cotw::Player::Black);
+
+    let response = client
+        .post(format!("{url}/games/{}/moves", state.id))
+        .bearer_auth(&beige.token)
+        .json(&move_)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let error: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(error["error"], "Beige tried to move but it is Black's turn");
+
+    let state: GameState = client
+        .get(format!("{url}/games/{}", state.id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(state.moves.len(), 1);
+}
+
+#[tokio::test]
+async fn errors() {
+    let url = start().await;
+    let client = reqwest::Client::new();
+
+    let response = client.get(format!("{url}/games/7")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .post(format!("{url}/games"))
+        .json(&json!({ "position": "3C3/2MMM2 beige" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(format!("{url}/games"))
+        .json(&json!({ "position": "2MMM2/1cm1m2/3mM2/7/7/7/7 black" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
@@ -0,0 +1,132 @@
+use backend::{ws::ServerMessage, GameState, Games};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn connect(addr: &str, id: u64, query: &str) -> Socket {
+    let (socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/games/{id}/ws{query}"))
+        .await
+        .unwrap();
+    socket
+}
+
+async fn next(socket: &mut Socket) -> ServerMessage {
+    use futures_util::StreamExt;
+    loop {
+        match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        }
+    }
+}
+
+async fn send(socket: &mut Socket, message: serde_json::Value) {
+    use futures_util::SinkExt;
+    socket
+        .send(Message::Text(message.to_string().into()))
+        .await
+        .unwrap();
+}
+
+fn state(message: ServerMessage) -> GameState {
+    match message {
+        ServerMessage::State(state) => state,
+        other => panic!("expected state, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn moves_are_pushed_to_everyone() {
+    let games = Arc::new(Games::default());
+    let (id, _) = games.create(cotw::Game::default());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(backend::serve(listener, games.clone()));
+
+    let beige_token = games.join(id, cotw::Player::Beige).unwrap();
+    let black_token = games.join(id, cotw::Player::Black).unwrap();
+    let mut beige = connect(&addr, id, &format!("?token={beige_token}")).await;
+    let mut black = connect(&addr, id, &format!("?token={black_token}")).await;
+    let mut spectator = connect(&addr, id, "").await;
+    for socket in [&mut beige, &mut black, &mut spectator] {
+        assert!(state(next(socket).await).moves.is_empty());
+    }
+
+    let move_ = json!({
+        "type": "move",
+        "move": {
+            "player": "Beige",
+            "messenger": "d5",
+            "direction": "S",
+            "first_throw": "N",
+            "extra_throws": ["NE"],
+        },
+    });
+
+    send(&mut black, move_.clone()).await;
+    assert!(matches!(
+        next(&mut black).await,
+        ServerMessage::Error { .. }
+    ));
+
+    send(&mut beige, move_.clone()).await;
+    for socket in [&mut beige, &mut black, &mut spectator] {
+        let state = state(next(socket).await);
+        assert_eq!(state.moves, ["d5-S/N,NE"]);
+        assert_eq!(state.to_move, cotw::Player::Black);
+        assert!(!state.legal_moves.is_empty());
+    }
+
+    send(&mut beige, move_).await;
+    match next(&mut beige).await {
+        ServerMessage::Error { error } => {
+            assert_eq!(error, "Beige tried to move but it is Black's turn")
+        }
+        other => panic!("expected error, got {:?}", other),
+    }
+
+    drop(black);
+    let mut black = connect(&addr, id, &format!("?token={black_token}")).await;
+    assert_eq!(state(next(&mut black).await).moves.len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_tokens_are_refused() {
+    let games = Arc::new(Games::default());
+    let (id, _) = games.create(cotw::Game::default());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(backend::serve(listener, games.clone()));
+
+    let connected =
+        tokio_tungstenite::connect_async(format!("ws://{addr}/games/{id}/ws?player=Beige")).await;
+    assert!(connected.is_ok());
+    let connected =
+        tokio_tungstenite::connect_async(format!("ws://{addr}/games/{id}/ws?token=guess")).await;
+    assert!(connected.is_err());
+
+    // claiming to be a player without a token makes you a spectator
+    let mut socket = connect(&addr, id, "?player=Beige").await;
+    assert!(state(next(&mut socket).await).moves.is_empty());
+    send(
+        &mut socket,
+        json!({
+            "type": "move",
+            "move": {
+                "player": "Beige",
+                "messenger": "d5",
+                "direction": "S",
+                "first_throw": "N",
+                "extra_throws": ["NE"],
+            },
+        }),
+    )
+    .await;
+    assert!(matches!(
+        next(&mut socket).await,
+        ServerMessage::Error { .. }
+    ));
+}
@@ -1,8 +1,9 @@
 import './app.css'
 import App from './App.svelte'
+import { init } from './lib/game'
 
-const app = new App({
+const app = init().then(() => new App({
   target: document.getElementById('app') as Element,
-})
+}))
 
 export default app
@@ -0,0 +1,53 @@
+//! Rough timings for move generation and search, run with `cargo bench`.
+
+use cotw::{search::search, Game, Player, SearchLimits, BAD_THROW_2, BAD_THROW_3};
+use std::{hint::black_box, time::Instant};
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!("{name:<32} {per_iteration:>12.2?}");
+}
+
+fn main() {
+    let positions = [
+        ("start", Game::default()),
+        (
+            "bad throw 2",
+            Game::from_position(BAD_THROW_2, Player::Black),
+        ),
+        (
+            "bad throw 3",
+            Game::from_position(BAD_THROW_3, Player::Black),
+        ),
+    ];
+
+    for (name, game) in positions {
+        bench(&format!("legal_moves {name}"), 100, || {
+            black_box(black_box(&game).legal_moves());
+        });
+    }
+
+    for (name, game) in positions {
+        let moves = game.legal_moves();
+        bench(&format!("make_move {name}"), 100, || {
+            for move_ in moves.iter() {
+                let mut game = black_box(game);
+                black_box(game.make_move(move_).unwrap());
+            }
+        });
+    }
+
+    let limits = SearchLimits {
+        depth: Some(2),
+        nodes: None,
+        time: None,
+    };
+    bench("search start depth 2", 3, || {
+        black_box(search(&Game::default(), limits));
+    });
+}
@@ -0,0 +1,140 @@
+//! An engine speaking the [protocol](cotw::protocol) over stdin and stdout. Run it as
+//! `cotw-engine [--threads <n>]` to search with more than one thread.
+
+use std::{
+    io::{BufRead, Write},
+    thread::JoinHandle,
+    time::Instant,
+};
+
+use cotw::{
+    protocol::{Command, Info, Response},
+    search::StopHandle,
+    Game, Heuristic, LegalMove, ParallelSearcher, SearchLimits,
+};
+
+fn send(response: Response) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{response}");
+    let _ = stdout.flush();
+}
+
+struct Engine {
+    /// The searcher moves to the search thread while it's searching, and comes back when it's
+    /// done.
+    searcher: Option<ParallelSearcher<Heuristic>>,
+    search: Option<JoinHandle<ParallelSearcher<Heuristic>>>,
+    stop: StopHandle,
+    /// `None` after a `position` with an illegal move, until the next valid one.
+    game: Option<Game>,
+}
+
+impl Engine {
+    /// Stop the current search, if any, and wait for it to send its best move. Waiting without
+    /// stopping could wait forever, since the `stop` would never be read.
+    fn searcher(&mut self) -> &mut ParallelSearcher<Heuristic> {
+        if let Some(search) = self.search.take() {
+            self.stop.stop();
+            self.searcher = Some(search.join().expect("search thread panicked"));
+        }
+        self.searcher
+            .as_mut()
+            .expect("the searcher is idle or searching")
+    }
+
+    fn position(&mut self, start: Option<Game>, moves: Vec<cotw::MoveNotation>) {
+        let mut game = start.unwrap_or_default();
+        for notation in moves {
+            let move_ = LegalMove::from_move(&game, notation.for_player(game.to_move()))
+                .and_then(|move_| game.make_move(&move_));
+            if let Err(err) = move_ {
+                send(Response::Message(format!("{notation} is illegal: {err}")));
+                self.game = None;
+                return;
+            }
+        }
+        self.game = Some(game);
+    }
+
+    fn go(&mut self, limits: SearchLimits) {
+        self.searcher();
+        let Some(game) = self.game else {
+            send(Response::Message("no valid position to search".into()));
+            send(Response::BestMove(None));
+            return;
+        };
+        let mut searcher = self.searcher.take().expect("the search has finished");
+        self.stop.reset();
+        self.search = Some(std::thread::spawn(move || {
+            let start = Instant::now();
+            let result = searcher.search_with_progress(&game, limits, |result| {
+                send(Response::Info(Info::from_result(result, start.elapsed())))
+            });
+            send(Response::BestMove(
+                result.best_move.map(|move_| move_.to_move().into()),
+            ));
+            searcher
+        }));
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let usage = || -> ! {
+        eprintln!("usage: cotw-engine [--threads <n>]");
+        std::process::exit(1);
+    };
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let threads = match &args[..] {
+        [] => 1,
+        [flag, threads] if flag == "--threads" => threads.parse().unwrap_or_else(|_| usage()),
+        _ => usage(),
+    };
+
+    let searcher = ParallelSearcher::new(Heuristic::default(), threads);
+    let mut engine = Engine {
+        stop: searcher.stop_handle(),
+        searcher: Some(searcher),
+        search: None,
+        game: Some(Game::default()),
+    };
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match line.parse::<Command>() {
+            Ok(command) => command,
+            Err(err) => {
+                send(Response::Message(err.to_string()));
+                continue;
+            }
+        };
+
+        match command {
+            Command::Cotw => {
+                send(Response::Id {
+                    name: format!("cotw {}", env!("CARGO_PKG_VERSION")),
+                });
+                send(Response::CotwOk);
+            }
+            Command::IsReady => send(Response::ReadyOk),
+            Command::NewGame => engine.searcher().table().clear(),
+            Command::Position { start, moves } => {
+                engine.searcher();
+                engine.position(start, moves);
+            }
+            Command::Go(limits) => engine.go(limits),
+            Command::Stop => engine.stop.stop(),
+            Command::Quit => break,
+        }
+    }
+
+    engine.searcher();
+}
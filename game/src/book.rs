@@ -0,0 +1,337 @@
+//! Opening books, mapping positions to the moves worth playing from them. A book is written one
+//! position per line: the position's [Zobrist hash](crate::zobrist) in hex, then each move in
+//! [`MoveNotation`] followed by its weight. Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # from 200 games
+//! 5a1c0f3e9d27b864 d5-S/N 12 d5-SE/NW,N 3
+//! ```
+//!
+//! A move's chance of being played is its share of the weight of every move in the position.
+
+use std::collections::HashMap;
+
+use crate::{
+    engine::Engine, rng::Rng, Game, GameHistory, GameRecord, LegalMove, MoveNotation,
+    NotationError, Player, RecordResult, ReplayError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    pub notation: MoveNotation,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpeningBook {
+    positions: HashMap<u64, Vec<BookMove>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookError {
+    InvalidHash {
+        line: usize,
+        text: String,
+    },
+    InvalidMove {
+        line: usize,
+        text: String,
+        error: NotationError,
+    },
+    InvalidWeight {
+        line: usize,
+        text: String,
+    },
+    MissingWeight {
+        line: usize,
+    },
+}
+
+impl std::fmt::Display for BookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BookError::InvalidHash { line, text } => {
+                write!(f, "line {}: {:?} is not a position hash", line, text)
+            }
+            BookError::InvalidMove { line, text, error } => {
+                write!(f, "line {}: {:?} is not a move: {}", line, text, error)
+            }
+            BookError::InvalidWeight { line, text } => {
+                write!(f, "line {}: {:?} is not a weight", line, text)
+            }
+            BookError::MissingWeight { line } => write!(f, "line {}: move has no weight", line),
+        }
+    }
+}
+
+impl std::error::Error for BookError {}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook::default()
+    }
+
+    /// The number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn insert(&mut self, hash: u64, moves: Vec<BookMove>) {
+        self.positions.insert(hash, moves);
+    }
+
+    /// The book's moves for `game` with their weights, leaving out any that aren't legal there.
+    pub fn probe(&self, game: &Game) -> Vec<(LegalMove, u32)> {
+        let Some(moves) = self.positions.get(&game.zobrist()) else {
+            return vec![];
+        };
+        moves
+            .iter()
+            .filter(|book_move| book_move.weight != 0)
+            .flat_map(|book_move| {
+                let move_ = book_move.notation.for_player(game.to_move());
+                Some((LegalMove::from_move(game, move_).ok()?, book_move.weight))
+            })
+            .collect()
+    }
+
+    /// A random move from the book for `game`, weighted by the moves' weights.
+    pub fn choose(&self, game: &Game, rng: &mut Rng) -> Option<LegalMove> {
+        let moves = self.probe(game);
+        let total = moves
+            .iter()
+            .map(|&(_, weight)| weight as usize)
+            .sum::<usize>();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = rng.below(total);
+        for (move_, weight) in moves {
+            match pick.checked_sub(weight as usize) {
+                Some(rest) => pick = rest,
+                None => return Some(move_),
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl std::fmt::Display for OpeningBook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut hashes = self.positions.keys().collect::<Vec<_>>();
+        hashes.sort();
+        for hash in hashes {
+            write!(f, "{:016x}", hash)?;
+            for book_move in self.positions[hash].iter() {
+                write!(f, " {} {}", book_move.notation, book_move.weight)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for OpeningBook {
+    type Err = BookError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut book = OpeningBook::new();
+        for (i, text) in s.lines().enumerate() {
+            let line = i + 1;
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = text.split_whitespace();
+            let hash = fields.next().unwrap_or_default();
+            let hash = u64::from_str_radix(hash, 16).map_err(|_| BookError::InvalidHash {
+                line,
+                text: hash.into(),
+            })?;
+            let mut moves = Vec::new();
+            while let Some(notation) = fields.next() {
+                let notation = notation.parse().map_err(|error| BookError::InvalidMove {
+                    line,
+                    text: notation.into(),
+                    error,
+                })?;
+                let weight = fields.next().ok_or(BookError::MissingWeight { line })?;
+                let weight = weight.parse().map_err(|_| BookError::InvalidWeight {
+                    line,
+                    text: weight.into(),
+                })?;
+                moves.push(BookMove { notation, weight });
+            }
+            book.insert(hash, moves);
+        }
+        Ok(book)
+    }
+}
+
+/// Builds a book from finished games. Each time a move is played in a position it's weighted by
+/// how the game went for the player who made it: 2 for a win, 1 for a draw or an unfinished game
+/// and nothing for a loss.
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    /// Only moves this early in a game go in the book.
+    pub max_plies: usize,
+    positions: HashMap<u64, Vec<BookMove>>,
+}
+
+impl BookBuilder {
+    pub fn new(max_plies: usize) -> BookBuilder {
+        BookBuilder {
+            max_plies,
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Add the moves of a game, which ended with `result`.
+    pub fn add_history(&mut self, history: &GameHistory, result: RecordResult) {
+        for ply in history.plies().iter().take(self.max_plies) {
+            let weight = match (result, ply.before.to_move()) {
+                (RecordResult::BeigeWon, Player::Beige)
+                | (RecordResult::BlackWon, Player::Black) => 2,
+                (RecordResult::BeigeWon | RecordResult::BlackWon, _) => 0,
+                (RecordResult::Draw | RecordResult::Unfinished, _) => 1,
+            };
+            let notation = MoveNotation::from(ply.move_.to_move());
+            let moves = self.positions.entry(ply.before.zobrist()).or_default();
+            match moves
+                .iter_mut()
+                .find(|book_move| book_move.notation == notation)
+            {
+                Some(book_move) => book_move.weight += weight,
+                None => moves.push(BookMove { notation, weight }),
+            }
+        }
+    }
+
+    /// Add the main line of a record, using its result.
+    pub fn add_record(&mut self, record: &GameRecord) -> Result<(), ReplayError> {
+        self.add_history(&record.replay()?, record.result);
+        Ok(())
+    }
+
+    /// The book of every move with any weight, heaviest first.
+    pub fn build(&self) -> OpeningBook {
+        let mut book = OpeningBook::new();
+        for (&hash, moves) in self.positions.iter() {
+            let mut moves = moves
+                .iter()
+                .filter(|book_move| book_move.weight != 0)
+                .copied()
+                .collect::<Vec<_>>();
+            if moves.is_empty() {
+                continue;
+            }
+            moves.sort_by_key(|book_move| std::cmp::Reverse(book_move.weight));
+            book.insert(hash, moves);
+        }
+        book
+    }
+}
+
+/// Plays from an opening book while it can, and lets another engine choose once the game leaves
+/// the book.
+pub struct BookEngine<E> {
+    pub book: OpeningBook,
+    pub engine: E,
+    rng: Rng,
+}
+
+impl<E: Engine> BookEngine<E> {
+    pub fn new(book: OpeningBook, engine: E, seed: u64) -> BookEngine<E> {
+        BookEngine {
+            book,
+            engine,
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl<E: Engine> Engine for BookEngine<E> {
+    fn choose_move(&mut self, game: &Game) -> Option<LegalMove> {
+        self.book
+            .choose(game, &mut self.rng)
+            .or_else(|| self.engine.choose_move(game))
+    }
+
+    fn new_game(&mut self) {
+        self.engine.new_game();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RECORDS: &str = "
+        [Result \"1-0\"]
+        1. d5-S/N 1-0
+
+        [Result \"0-1\"]
+        1. d5-S/N 0-1
+
+        [Result \"1/2-1/2\"]
+        1. d5-W/SW 1/2-1/2
+    ";
+
+    #[test]
+    fn build_and_probe() {
+        let mut builder = BookBuilder::new(1);
+        for record in GameRecord::parse_all(RECORDS).unwrap() {
+            builder.add_record(&record).unwrap();
+        }
+        let book = builder.build();
+        assert_eq!(book.len(), 1);
+        assert_eq!(book.to_string().parse::<OpeningBook>().unwrap(), book);
+
+        let game = Game::default();
+        let moves = book
+            .probe(&game)
+            .into_iter()
+            .map(|(move_, weight)| (move_.to_string(), weight))
+            .collect::<Vec<_>>();
+        assert_eq!(moves, [("d5-S/N".into(), 2), ("d5-W/SW".into(), 1)]);
+
+        let mut rng = Rng::new(21);
+        let mut picks = [0; 2];
+        for _ in 0..300 {
+            let move_ = book.choose(&game, &mut rng).unwrap();
+            picks[(move_.to_string() == "d5-W/SW") as usize] += 1;
+        }
+        assert!(picks[0] > picks[1] && picks[1] > 0);
+    }
+
+    #[test]
+    fn engine_leaves_the_book() {
+        struct First;
+        impl Engine for First {
+            fn choose_move(&mut self, game: &Game) -> Option<LegalMove> {
+                game.legal_moves().first().copied()
+            }
+        }
+
+        let book =
+            "# test\n\n".to_string() + &format!("{:016x} d5-S/N 1", Game::default().zobrist());
+        let mut engine = BookEngine::new(book.parse().unwrap(), First, 0);
+        let mut game = Game::default();
+        let move_ = engine.choose_move(&game).unwrap();
+        assert_eq!(move_.to_string(), "d5-S/N");
+        game.make_move(&move_).unwrap();
+        assert_eq!(
+            engine.choose_move(&game),
+            game.legal_moves().first().copied()
+        );
+
+        assert_eq!(
+            "0 d5-S/N".parse::<OpeningBook>(),
+            Err(BookError::MissingWeight { line: 1 })
+        );
+    }
+}
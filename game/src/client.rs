@@ -0,0 +1,166 @@
+//! Driving engines that speak the [protocol](crate::protocol) as subprocesses.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Stdio},
+};
+
+use crate::{
+    protocol::{Command, Info, Response},
+    Engine, Game, LegalMove, MoveNotation, SearchLimits,
+};
+
+/// What an engine said in response to `go`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoResult {
+    /// `None` if the game is over, or if the engine rejected the position.
+    pub best_move: Option<MoveNotation>,
+    /// The last `info` line sent before the best move.
+    pub info: Option<Info>,
+}
+
+pub struct EngineProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    name: String,
+    /// The limits used by [`Engine::choose_move`], depth 3 to start with. Without any limits the
+    /// engine searches until it's told to stop, which `choose_move` never does.
+    pub limits: SearchLimits,
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+impl EngineProcess {
+    /// Start an engine and wait for it to introduce itself.
+    pub fn spawn(command: &mut std::process::Command) -> io::Result<EngineProcess> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+
+        let mut engine = EngineProcess {
+            child,
+            stdin,
+            stdout,
+            name: String::new(),
+            limits: SearchLimits {
+                depth: Some(3),
+                ..Default::default()
+            },
+        };
+        engine.send(&Command::Cotw)?;
+        loop {
+            match engine.recv()? {
+                Response::Id { name } => engine.name = name,
+                Response::CotwOk => break,
+                _ => {}
+            }
+        }
+        Ok(engine)
+    }
+
+    /// The name the engine gave in its `id` response.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn send(&mut self, command: &Command) -> io::Result<()> {
+        tracing::trace!("> {command}");
+        writeln!(self.stdin, "{command}")?;
+        self.stdin.flush()
+    }
+
+    /// Wait for the next response, skipping lines that aren't part of the protocol.
+    pub fn recv(&mut self) -> io::Result<Response> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            tracing::trace!("< {}", line.trim_end());
+            match line.parse() {
+                Ok(response) => return Ok(response),
+                Err(err) => tracing::debug!("ignoring {:?}: {err}", line.trim_end()),
+            }
+        }
+    }
+
+    /// Wait until the engine is ready for more commands.
+    pub fn is_ready(&mut self) -> io::Result<()> {
+        self.send(&Command::IsReady)?;
+        while self.recv()? != Response::ReadyOk {}
+        Ok(())
+    }
+
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send(&Command::NewGame)
+    }
+
+    pub fn set_position(&mut self, game: &Game) -> io::Result<()> {
+        self.send(&Command::Position {
+            start: Some(*game),
+            moves: Vec::new(),
+        })
+    }
+
+    /// Search the current position, waiting for the best move.
+    pub fn go(&mut self, limits: SearchLimits) -> io::Result<GoResult> {
+        self.send(&Command::Go(limits))?;
+        let mut info = None;
+        loop {
+            match self.recv()? {
+                Response::Info(line) => info = Some(line),
+                Response::Message(message) => tracing::debug!("{}: {message}", self.name),
+                Response::BestMove(best_move) => return Ok(GoResult { best_move, info }),
+                _ => {}
+            }
+        }
+    }
+
+    /// Ask the engine to stop searching. The best move still has to be read.
+    pub fn stop(&mut self) -> io::Result<()> {
+        self.send(&Command::Stop)
+    }
+
+    fn choose(&mut self, game: &Game) -> io::Result<Option<LegalMove>> {
+        self.set_position(game)?;
+        let Some(notation) = self.go(self.limits)?.best_move else {
+            return Ok(None);
+        };
+        LegalMove::from_move(game, notation.for_player(game.to_move()))
+            .map(Some)
+            .map_err(|err| invalid_data(format!("{notation} is illegal: {err}")))
+    }
+}
+
+impl Engine for EngineProcess {
+    fn choose_move(&mut self, game: &Game) -> Option<LegalMove> {
+        match self.choose(game) {
+            Ok(move_) => move_,
+            Err(err) => {
+                tracing::error!("{}: {err}", self.name);
+                None
+            }
+        }
+    }
+
+    fn new_game(&mut self) {
+        if let Err(err) = EngineProcess::new_game(self).and_then(|_| self.is_ready()) {
+            tracing::error!("{}: {err}", self.name);
+        }
+    }
+}
+
+impl Drop for EngineProcess {
+    fn drop(&mut self) {
+        if self.send(&Command::Quit).is_err() || self.child.wait().is_err() {
+            let _ = self.child.kill();
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! A common interface for everything that can pick a move.
+
+use crate::{
+    eval::{Evaluator, Heuristic},
+    search::{SearchLimits, Searcher},
+    Game, LegalMove,
+};
+
+pub trait Engine {
+    /// Pick a move for the player to move, or `None` if the game is over.
+    fn choose_move(&mut self, game: &Game) -> Option<LegalMove>;
+
+    /// Forget anything kept from earlier games, before starting another.
+    fn new_game(&mut self) {}
+}
+
+/// Alpha-beta search, see [`Searcher`].
+pub struct AlphaBeta<E> {
+    pub searcher: Searcher<E>,
+    pub limits: SearchLimits,
+}
+
+impl AlphaBeta<Heuristic> {
+    pub fn new(limits: SearchLimits) -> AlphaBeta<Heuristic> {
+        AlphaBeta::with_evaluator(Heuristic::default(), limits)
+    }
+}
+
+impl<E: Evaluator> AlphaBeta<E> {
+    pub fn with_evaluator(evaluator: E, limits: SearchLimits) -> AlphaBeta<E> {
+        AlphaBeta {
+            searcher: Searcher::new(evaluator),
+            limits,
+        }
+    }
+}
+
+impl<E: Evaluator> Engine for AlphaBeta<E> {
+    fn choose_move(&mut self, game: &Game) -> Option<LegalMove> {
+        self.searcher.search(game, self.limits).best_move
+    }
+
+    fn new_game(&mut self) {
+        self.searcher.table().clear();
+    }
+}
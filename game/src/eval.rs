@@ -0,0 +1,126 @@
+//! Position evaluation for the search.
+
+use crate::{search::Score, BoardCoordinate, Game, Player};
+
+pub trait Evaluator {
+    /// Score `game` from the point of view of the player to move. Finished games are scored by
+    /// the search, so this is only called on games which are still going.
+    fn evaluate(&self, game: &Game) -> Score;
+}
+
+impl<F: Fn(&Game) -> Score> Evaluator for F {
+    fn evaluate(&self, game: &Game) -> Score {
+        self(game)
+    }
+}
+
+/// How much each feature of a position is worth to [`Heuristic`]. Each player's features are
+/// scored separately and their opponent's score is subtracted from theirs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weights {
+    /// Per row the player's cylinder is from its home row.
+    pub cylinder_advancement: Score,
+    pub messenger: Score,
+    pub stunned_messenger: Score,
+    /// Per legal move the player would have if it were their turn.
+    pub mobility: Score,
+    /// Per extra throw in each of those legal moves.
+    pub throw_chain: Score,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            cylinder_advancement: 100,
+            messenger: 10,
+            stunned_messenger: 4,
+            mobility: 1,
+            throw_chain: 2,
+        }
+    }
+}
+
+/// The default evaluation. Setting both [`Weights::mobility`] and [`Weights::throw_chain`] to 0
+/// makes it much faster, since the other features don't need any moves to be generated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Heuristic {
+    pub weights: Weights,
+}
+
+impl Heuristic {
+    pub fn new(weights: Weights) -> Heuristic {
+        Heuristic { weights }
+    }
+
+    fn player_score(&self, game: &Game, player: Player) -> Score {
+        let weights = &self.weights;
+        let mut score = 0;
+        let mut messengers = Vec::new();
+
+        for coord in (1..=7).flat_map(|y| (1..=7).flat_map(move |x| BoardCoordinate::new(x, y))) {
+            let square = game.board[coord];
+            if !square.is_players(player) {
+                continue;
+            }
+            if square.is_cylinder() {
+                let advancement = (coord.y() - player.home_row()).abs() as Score;
+                score += weights.cylinder_advancement * advancement;
+            } else if square.is_unstunned_messenger() {
+                score += weights.messenger;
+                messengers.push(coord);
+            } else if square.is_stunned_messenger() {
+                score += weights.stunned_messenger;
+            }
+        }
+
+        if weights.mobility != 0 || weights.throw_chain != 0 {
+            let game = Game {
+                to_move: player,
+                ..*game
+            };
+            for messenger in messengers {
+                for move_ in game.legal_moves_for(messenger) {
+                    let extra_throws = match move_.extra_throws {
+                        None => 0,
+                        Some((_, None)) => 1,
+                        Some((_, Some((_, None)))) => 2,
+                        Some((_, Some((_, Some(_))))) => 3,
+                    };
+                    score += weights.mobility + weights.throw_chain * extra_throws;
+                }
+            }
+        }
+
+        score
+    }
+}
+
+impl Evaluator for Heuristic {
+    fn evaluate(&self, game: &Game) -> Score {
+        self.player_score(game, game.to_move) - self.player_score(game, game.to_move.opponent())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn heuristic() {
+        let heuristic = Heuristic::default();
+        assert_eq!(heuristic.evaluate(&Game::default()), 0);
+
+        let material = Heuristic::new(Weights {
+            mobility: 0,
+            throw_chain: 0,
+            ..Weights::default()
+        });
+        let game = Game::from_position_string("7/2m4/3c3/7/6M/3C3/7 black").unwrap();
+        // black's cylinder is 4 rows from home, beige's 5, and both have one messenger
+        assert_eq!(material.evaluate(&game), -100);
+
+        let game = Game::from_position_string("3C3/2MMM2/3s3/7/7/2mmm2/3c3 black").unwrap();
+        assert_eq!(material.evaluate(&game), 34 - 30);
+    }
+}
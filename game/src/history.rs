@@ -0,0 +1,173 @@
+use crate::{Game, LegalMove, MoveError, Outcome};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ply {
+    pub move_: LegalMove,
+    pub before: Game,
+    pub after: Game,
+}
+
+/// A game along with every move made in it. Undone moves are kept so they can be redone until a
+/// different move is made.
+#[derive(Debug, Clone)]
+pub struct GameHistory {
+    start: Game,
+    plies: Vec<Ply>,
+    current: usize,
+}
+
+impl Default for GameHistory {
+    fn default() -> Self {
+        GameHistory::new(Game::default())
+    }
+}
+
+impl GameHistory {
+    pub fn new(start: Game) -> GameHistory {
+        GameHistory {
+            start,
+            plies: Vec::new(),
+            current: 0,
+        }
+    }
+
+    pub fn start(&self) -> &Game {
+        &self.start
+    }
+
+    pub fn game(&self) -> &Game {
+        match self.current {
+            0 => &self.start,
+            n => &self.plies[n - 1].after,
+        }
+    }
+
+    /// The number of moves made to reach the current position.
+    pub fn ply(&self) -> usize {
+        self.current
+    }
+
+    /// The number of moves recorded, including ones which have been undone.
+    pub fn len(&self) -> usize {
+        self.plies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plies.is_empty()
+    }
+
+    pub fn plies(&self) -> &[Ply] {
+        &self.plies
+    }
+
+    pub fn make_move(&mut self, move_: &LegalMove) -> Result<Option<Outcome>, MoveError> {
+        let before = *self.game();
+        let mut after = before;
+        let outcome = after.make_move(move_)?;
+
+        self.plies.truncate(self.current);
+        self.plies.push(Ply {
+            move_: *move_,
+            before,
+            after,
+        });
+        self.current += 1;
+
+        Ok(outcome)
+    }
+
+    pub fn undo(&mut self) -> Option<&Ply> {
+        if self.current == 0 {
+            return None;
+        }
+        self.current -= 1;
+        Some(&self.plies[self.current])
+    }
+
+    pub fn redo(&mut self) -> Option<&Ply> {
+        if self.current == self.plies.len() {
+            return None;
+        }
+        self.current += 1;
+        Some(&self.plies[self.current - 1])
+    }
+
+    pub fn jump_to(&mut self, ply: usize) -> Option<&Game> {
+        if ply > self.plies.len() {
+            return None;
+        }
+        self.current = ply;
+        Some(self.game())
+    }
+
+    /// Every position from the start of the game up to and including the current one.
+    pub fn positions(&self) -> impl Iterator<Item = &Game> + '_ {
+        std::iter::once(&self.start).chain(self.plies[..self.current].iter().map(|ply| &ply.after))
+    }
+
+    /// Every move made to reach the current position.
+    pub fn moves(&self) -> impl Iterator<Item = &LegalMove> + '_ {
+        self.plies[..self.current].iter().map(|ply| &ply.move_)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BoardCoordinate, Direction, Move, Piece, Player, Square};
+
+    #[test]
+    fn undo_redo() {
+        let mut history = GameHistory::default();
+        let first = LegalMove::from_move(
+            history.game(),
+            Move {
+                player: Player::Beige,
+                messenger: BoardCoordinate::new(4, 5).unwrap(),
+                direction: Direction::S,
+                first_throw: Direction::N,
+                extra_throws: Some((Direction::NE, None)),
+            },
+        )
+        .unwrap();
+        history.make_move(&first).unwrap();
+        let second = LegalMove::from_move(
+            history.game(),
+            Move {
+                player: Player::Black,
+                messenger: BoardCoordinate::new(4, 2).unwrap(),
+                direction: Direction::N,
+                first_throw: Direction::S,
+                extra_throws: Some((Direction::W, None)),
+            },
+        )
+        .unwrap();
+        history.make_move(&second).unwrap();
+
+        let stunned = BoardCoordinate::new(6, 7).unwrap();
+        assert_eq!(history.ply(), 2);
+        assert_eq!(history.positions().count(), 3);
+        assert_eq!(
+            history.plies()[0].after.board()[stunned],
+            Square::Black(Piece::StunnedMessenger)
+        );
+
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+        assert_eq!(history.game().to_move(), Player::Beige);
+        assert!(history.game().board()[stunned].is_empty());
+
+        assert!(history.redo().is_some());
+        assert_eq!(history.game().to_move(), Player::Black);
+        assert_eq!(history.moves().count(), 1);
+
+        assert!(history.jump_to(3).is_none());
+        assert_eq!(history.jump_to(2).unwrap().to_move(), Player::Beige);
+        assert!(history.redo().is_none());
+
+        history.jump_to(1);
+        history.make_move(&second).unwrap();
+        assert_eq!(history.len(), 2);
+    }
+}
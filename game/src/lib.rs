@@ -1,4 +1,46 @@
+pub mod book;
+pub mod client;
+pub mod engine;
+pub mod eval;
+pub mod history;
+pub mod mcts;
+pub mod movegen;
+pub mod notation;
+pub mod parallel;
+pub mod perft;
+pub mod position;
+pub mod protocol;
+pub mod record;
+pub mod rng;
+pub mod search;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod tablebase;
+pub mod tournament;
+pub mod tt;
+pub mod zobrist;
+
+pub use book::{BookBuilder, BookEngine, BookError, BookMove, OpeningBook};
+pub use client::EngineProcess;
+pub use engine::{AlphaBeta, Engine};
+pub use eval::{Evaluator, Heuristic, Weights};
+pub use history::{GameHistory, Ply};
+pub use mcts::{Mcts, MctsConfig};
+pub use movegen::{MoveGroup, MoveOrder, Moves};
+pub use notation::{MoveNotation, NotationError};
+pub use parallel::ParallelSearcher;
+pub use position::PositionError;
+pub use protocol::ProtocolError;
+pub use record::{GameRecord, RecordError, RecordResult, RecordedMove, ReplayError};
+pub use search::{Score, SearchLimits, SearchResult, Searcher};
+#[cfg(feature = "serde")]
+pub use serialize::LegalMoveSeed;
+pub use tablebase::{Table, TableError, TableResult, Tablebase};
+pub use tournament::{Contestant, Match, MatchConfig, MatchResult, Sprt};
+pub use tt::TranspositionTable;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Square {
     Empty,
     Beige(Piece),
@@ -52,15 +94,54 @@ impl Square {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     Cylinder,
     Messenger,
     StunnedMessenger,
 }
 
-#[derive(Clone, Copy)]
+/// Every kind of piece, in the order of [`Board`]'s masks.
+const PIECES: &[Square; 6] = {
+    use Piece::*;
+    use Square::*;
+    &[
+        Beige(Cylinder),
+        Beige(Messenger),
+        Beige(StunnedMessenger),
+        Black(Cylinder),
+        Black(Messenger),
+        Black(StunnedMessenger),
+    ]
+};
+
+/// The index of `square` in [`PIECES`], `None` if it's empty.
+pub(crate) const fn piece_index(square: Square) -> Option<usize> {
+    match square {
+        Square::Empty => None,
+        Square::Beige(Piece::Cylinder) => Some(0),
+        Square::Beige(Piece::Messenger) => Some(1),
+        Square::Beige(Piece::StunnedMessenger) => Some(2),
+        Square::Black(Piece::Cylinder) => Some(3),
+        Square::Black(Piece::Messenger) => Some(4),
+        Square::Black(Piece::StunnedMessenger) => Some(5),
+    }
+}
+
+/// A set of squares. Square `board[row][col]` of [`Board::squares`] is bit `row * 7 + col`, see
+/// [`BoardCoordinate::index`].
+pub(crate) type Mask = u64;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "[[Square; 7]; 7]", from = "[[Square; 7]; 7]")
+)]
 pub struct Board {
-    board: [[Square; 7]; 7],
+    /// Where each kind of piece is, in the order of [`PIECES`].
+    masks: [Mask; 6],
+    hash: u64,
 }
 
 #[rustfmt::skip]
@@ -68,17 +149,15 @@ pub const STARTING_BOARD: Board = {
     use Piece::*;
     use Square::*;
 
-    Board {
-        board: [
-            [Empty, Empty, Empty,            Beige(Cylinder),  Empty,            Empty, Empty],
-            [Empty, Empty, Beige(Messenger), Beige(Messenger), Beige(Messenger), Empty, Empty],
-            [Empty, Empty, Empty,            Beige(Messenger), Empty,            Empty, Empty],
-            [Empty, Empty, Empty,            Empty,            Empty,            Empty, Empty],
-            [Empty, Empty, Empty,            Black(Messenger), Empty,            Empty, Empty],
-            [Empty, Empty, Black(Messenger), Black(Messenger), Black(Messenger), Empty, Empty],
-            [Empty, Empty, Empty,            Black(Cylinder),  Empty,            Empty, Empty],
-        ],
-    }
+    Board::from_squares([
+        [Empty, Empty, Empty,            Beige(Cylinder),  Empty,            Empty, Empty],
+        [Empty, Empty, Beige(Messenger), Beige(Messenger), Beige(Messenger), Empty, Empty],
+        [Empty, Empty, Empty,            Beige(Messenger), Empty,            Empty, Empty],
+        [Empty, Empty, Empty,            Empty,            Empty,            Empty, Empty],
+        [Empty, Empty, Empty,            Black(Messenger), Empty,            Empty, Empty],
+        [Empty, Empty, Black(Messenger), Black(Messenger), Black(Messenger), Empty, Empty],
+        [Empty, Empty, Empty,            Black(Cylinder),  Empty,            Empty, Empty],
+    ])
 };
 
 #[rustfmt::skip]
@@ -86,17 +165,15 @@ pub const BAD_THROW_2: Board = {
     use Piece::*;
     use Square::*;
 
-    Board {
-        board: [
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-            [Empty, Black(Cylinder), Black(Messenger), Empty,            Empty,            Empty, Empty],
-            [Empty, Empty,           Empty,            Black(Messenger), Beige(Messenger), Empty, Empty],
-            [Empty, Empty,           Beige(Messenger), Empty,            Beige(Messenger), Empty, Empty],
-            [Empty, Empty,           Beige(Messenger), Beige(Messenger), Beige(Messenger), Empty, Empty],
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-        ],
-    }
+    Board::from_squares([
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+        [Empty, Black(Cylinder), Black(Messenger), Empty,            Empty,            Empty, Empty],
+        [Empty, Empty,           Empty,            Black(Messenger), Beige(Messenger), Empty, Empty],
+        [Empty, Empty,           Beige(Messenger), Empty,            Beige(Messenger), Empty, Empty],
+        [Empty, Empty,           Beige(Messenger), Beige(Messenger), Beige(Messenger), Empty, Empty],
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+    ])
 };
 
 #[rustfmt::skip]
@@ -104,73 +181,163 @@ pub const BAD_THROW_3: Board = {
     use Piece::*;
     use Square::*;
 
-    Board {
-        board: [
-            [Empty, Empty,           Beige(Messenger), Beige(Messenger), Beige(Messenger), Empty, Empty],
-            [Empty, Black(Cylinder), Black(Messenger), Empty,            Black(Messenger), Empty, Empty],
-            [Empty, Empty,           Empty,            Black(Messenger), Beige(Messenger), Empty, Empty],
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-            [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
-        ],
-    }
+    Board::from_squares([
+        [Empty, Empty,           Beige(Messenger), Beige(Messenger), Beige(Messenger), Empty, Empty],
+        [Empty, Black(Cylinder), Black(Messenger), Empty,            Black(Messenger), Empty, Empty],
+        [Empty, Empty,           Empty,            Black(Messenger), Beige(Messenger), Empty, Empty],
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+        [Empty, Empty,           Empty,            Empty,            Empty,            Empty, Empty],
+    ])
 };
 
 impl std::ops::Index<BoardCoordinate> for Board {
     type Output = Square;
     fn index(&self, index: BoardCoordinate) -> &Self::Output {
-        &self.board[(7 - index.y) as usize][(index.x - 1) as usize]
+        self.square(index.index())
     }
 }
 
-impl std::ops::IndexMut<BoardCoordinate> for Board {
-    fn index_mut(&mut self, index: BoardCoordinate) -> &mut Self::Output {
-        &mut self.board[(7 - index.y) as usize][(index.x - 1) as usize]
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl From<[[Square; 7]; 7]> for Board {
+    fn from(board: [[Square; 7]; 7]) -> Self {
+        Board::from_squares(board)
+    }
+}
+
+impl From<Board> for [[Square; 7]; 7] {
+    fn from(board: Board) -> Self {
+        board.squares()
     }
 }
 
 impl Board {
-    fn move_piece(&self, from: BoardCoordinate, to: BoardCoordinate) -> Board {
+    /// A board from its rows, starting with the 7th.
+    pub const fn from_squares(board: [[Square; 7]; 7]) -> Board {
+        let mut masks = [0; 6];
+        let mut hash = 0;
+        let mut index = 0;
+        while index < 49 {
+            let square = board[index / 7][index % 7];
+            if let Some(piece) = piece_index(square) {
+                masks[piece] |= 1 << index;
+            }
+            hash ^= zobrist::square_key(index, square);
+            index += 1;
+        }
+        Board { masks, hash }
+    }
+
+    /// A board from masks in the order of [`PIECES`], which mustn't overlap.
+    pub(crate) fn from_masks(masks: [Mask; 6]) -> Board {
+        let mut hash = 0;
+        for (piece, &mask) in masks.iter().enumerate() {
+            let mut mask = mask;
+            while mask != 0 {
+                hash ^= zobrist::square_key(mask.trailing_zeros() as usize, PIECES[piece]);
+                mask &= mask - 1;
+            }
+        }
+        Board { masks, hash }
+    }
+
+    /// The rows of the board, starting with the 7th.
+    pub fn squares(&self) -> [[Square; 7]; 7] {
+        let mut squares = [[Square::Empty; 7]; 7];
+        for (index, square) in squares.iter_mut().flatten().enumerate() {
+            *square = *self.square(index);
+        }
+        squares
+    }
+
+    /// Put `square` at `at`. Boards can't be indexed mutably, since the masks and the hash have to
+    /// be updated together.
+    pub fn set(&mut self, at: BoardCoordinate, square: Square) {
+        self.set_index(at.index(), square);
+    }
+
+    /// The Zobrist hash of the pieces on the board, see [`zobrist`].
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    fn square(&self, index: usize) -> &'static Square {
+        match self.masks.iter().position(|mask| mask & (1 << index) != 0) {
+            Some(piece) => &PIECES[piece],
+            None => &Square::Empty,
+        }
+    }
+
+    pub(crate) fn pieces(&self, square: Square) -> Mask {
+        match piece_index(square) {
+            Some(piece) => self.masks[piece],
+            None => !self.occupied() & ((1 << 49) - 1),
+        }
+    }
+
+    pub(crate) fn occupied(&self) -> Mask {
+        self.masks.iter().fold(0, |occupied, mask| occupied | mask)
+    }
+
+    /// Stunned or not.
+    pub(crate) fn messengers(&self, player: Player) -> Mask {
+        match player {
+            Player::Beige => self.masks[1] | self.masks[2],
+            Player::Black => self.masks[4] | self.masks[5],
+        }
+    }
+
+    fn set_index(&mut self, index: usize, square: Square) {
+        let old = *self.square(index);
+        if let Some(piece) = piece_index(old) {
+            self.masks[piece] &= !(1 << index);
+        }
+        if let Some(piece) = piece_index(square) {
+            self.masks[piece] |= 1 << index;
+        }
+        self.hash ^= zobrist::square_key(index, old);
+        self.hash ^= zobrist::square_key(index, square);
+    }
+
+    pub(crate) fn move_piece(&self, from: usize, to: usize) -> Board {
         let mut new_board = *self;
-        let piece = new_board[from];
-        new_board[from] = Square::Empty;
-        new_board[to] = piece;
+        let piece = *self.square(from);
+        new_board.set_index(from, Square::Empty);
+        new_board.set_index(to, piece);
         new_board
     }
 
-    fn stun_if_opponents(&self, at: BoardCoordinate, player: Player) -> Board {
-        match self[at] {
-            Square::Beige(Piece::Messenger) if player.opponent() == Player::Beige => {
-                let mut new_board = *self;
-                new_board[at] = Square::Beige(Piece::StunnedMessenger);
-                new_board
+    pub(crate) fn stun_if_opponents(&self, at: usize, player: Player) -> Board {
+        let mut new_board = *self;
+        match player.opponent() {
+            Player::Beige if self.masks[1] & (1 << at) != 0 => {
+                new_board.set_index(at, Square::Beige(Piece::StunnedMessenger))
             }
-
-            Square::Black(Piece::Messenger) if player.opponent() == Player::Black => {
-                let mut new_board = *self;
-                new_board[at] = Square::Black(Piece::StunnedMessenger);
-                new_board
+            Player::Black if self.masks[4] & (1 << at) != 0 => {
+                new_board.set_index(at, Square::Black(Piece::StunnedMessenger))
             }
-
-            _ => *self,
+            _ => {}
         }
+        new_board
     }
 
     fn un_stun(&self, player: Player) -> Board {
+        use Piece::*;
+        let (stunned, un_stunned) = match player {
+            Player::Beige => (Square::Beige(StunnedMessenger), Square::Beige(Messenger)),
+            Player::Black => (Square::Black(StunnedMessenger), Square::Black(Messenger)),
+        };
         let mut new_board = *self;
-        for row in new_board.board.iter_mut() {
-            for square in row.iter_mut() {
-                *square = match square {
-                    Square::Beige(Piece::StunnedMessenger) if player == Player::Beige => {
-                        Square::Beige(Piece::Messenger)
-                    }
-                    Square::Black(Piece::StunnedMessenger) if player == Player::Black => {
-                        Square::Black(Piece::Messenger)
-                    }
-                    _ => *square,
-                };
-            }
+        let mut mask = self.pieces(stunned);
+        while mask != 0 {
+            new_board.set_index(mask.trailing_zeros() as usize, un_stunned);
+            mask &= mask - 1;
         }
         new_board
     }
@@ -181,7 +348,7 @@ impl std::fmt::Debug for Board {
         if f.alternate() {
             writeln!(f)?;
             let mut y = 7;
-            for row in self.board {
+            for row in self.squares() {
                 for square in row {
                     match square {
                         Square::Empty => write!(f, " ")?,
@@ -203,7 +370,7 @@ impl std::fmt::Debug for Board {
             writeln!(f, "1234567")?;
             Ok(())
         } else {
-            f.debug_list().entries(self.board.iter()).finish()
+            f.debug_list().entries(self.squares().iter()).finish()
         }
     }
 }
@@ -222,6 +389,26 @@ impl BoardCoordinate {
         }
     }
 
+    pub fn x(&self) -> i8 {
+        self.x
+    }
+
+    pub fn y(&self) -> i8 {
+        self.y
+    }
+
+    /// The bit for this square in a [`Mask`].
+    pub(crate) fn index(&self) -> usize {
+        (7 - self.y) as usize * 7 + (self.x - 1) as usize
+    }
+
+    pub(crate) fn from_index(index: usize) -> BoardCoordinate {
+        BoardCoordinate {
+            x: (index % 7) as i8 + 1,
+            y: 7 - (index / 7) as i8,
+        }
+    }
+
     pub fn one_away(&self, other: BoardCoordinate) -> bool {
         ONE_SQUARE.iter().any(|dir| *self + *dir == Some(other))
     }
@@ -235,7 +422,7 @@ impl std::fmt::Debug for BoardCoordinate {
 
 impl std::fmt::Display for BoardCoordinate {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{},{}", self.x, self.y)
+        write!(f, "{}{}", (b'a' + self.x as u8 - 1) as char, self.y)
     }
 }
 
@@ -290,6 +477,7 @@ impl std::ops::Neg for Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[rustfmt::skip]
 pub enum Direction {
     NW2,     N2,     NE2,
@@ -328,7 +516,12 @@ pub const TWO_SQUARES: [Direction; 8] = {
 
 pub type ExtraThrows = Option<(Direction, Option<(Direction, Option<Direction>)>)>;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "serialize::MoveRepr", try_from = "serialize::MoveRepr")
+)]
 pub struct Move {
     pub player: Player,
     pub messenger: BoardCoordinate,
@@ -337,13 +530,13 @@ pub struct Move {
     pub extra_throws: ExtraThrows,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LegalMove(Move);
 
 impl LegalMove {
-    pub fn from_move(game: &Game, move_: Move) -> Option<LegalMove> {
+    pub fn from_move(game: &Game, move_: Move) -> Result<LegalMove, MoveError> {
         let _new_board = game.attempt_move(&move_)?;
-        Some(LegalMove(move_))
+        Ok(LegalMove(move_))
     }
 
     pub fn to_move(self) -> Move {
@@ -359,6 +552,167 @@ impl std::ops::Deref for LegalMove {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveError {
+    GameOver(Outcome),
+    WrongPlayer {
+        to_move: Player,
+        player: Player,
+    },
+    NotPlayersPiece {
+        at: BoardCoordinate,
+    },
+    NotAMessenger {
+        at: BoardCoordinate,
+    },
+    StunnedMessenger {
+        at: BoardCoordinate,
+    },
+    MoveOffBoard {
+        messenger: BoardCoordinate,
+        direction: Direction,
+    },
+    MoveToOccupied {
+        to: BoardCoordinate,
+    },
+    HopOverOccupied {
+        over: BoardCoordinate,
+    },
+    NotPlayersThrower {
+        throw: usize,
+        at: BoardCoordinate,
+    },
+    ThrowNotUnitLength {
+        throw: usize,
+        direction: Direction,
+    },
+    ThrowFromOffBoard {
+        throw: usize,
+        messenger: BoardCoordinate,
+        direction: Direction,
+    },
+    ThrowOffBoard {
+        throw: usize,
+        messenger: BoardCoordinate,
+        direction: Direction,
+    },
+    CannotThrowPiece {
+        throw: usize,
+        from: BoardCoordinate,
+    },
+    ThrowToOccupied {
+        throw: usize,
+        to: BoardCoordinate,
+    },
+    NoMajority {
+        throw: usize,
+        at: BoardCoordinate,
+        mine: usize,
+        theirs: usize,
+    },
+    ThrowerOffBoard {
+        throw: usize,
+        from: BoardCoordinate,
+        direction: Direction,
+    },
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::GameOver(outcome) => write!(f, "the game is over: {:?}", outcome),
+            MoveError::WrongPlayer { to_move, player } => {
+                write!(
+                    f,
+                    "{:?} tried to move but it is {:?}'s turn",
+                    player, to_move
+                )
+            }
+            MoveError::NotPlayersPiece { at } => {
+                write!(f, "the current player has no piece at {}", at)
+            }
+            MoveError::NotAMessenger { at } => write!(f, "the piece at {} is not a messenger", at),
+            MoveError::StunnedMessenger { at } => write!(f, "the messenger at {} is stunned", at),
+            MoveError::MoveOffBoard {
+                messenger,
+                direction,
+            } => write!(
+                f,
+                "the messenger at {} cannot move {:?} off the board",
+                messenger, direction
+            ),
+            MoveError::MoveToOccupied { to } => {
+                write!(f, "the messenger cannot move to {}, it is occupied", to)
+            }
+            MoveError::HopOverOccupied { over } => {
+                write!(f, "the messenger cannot hop over {}, it is occupied", over)
+            }
+            MoveError::NotPlayersThrower { throw, at } => write!(
+                f,
+                "throw {} must be made by one of the player's messengers, but {} is not",
+                throw, at
+            ),
+            MoveError::ThrowNotUnitLength { throw, direction } => {
+                write!(
+                    f,
+                    "throw {} ({:?}) is not one square long",
+                    throw, direction
+                )
+            }
+            MoveError::ThrowFromOffBoard {
+                throw,
+                messenger,
+                direction,
+            } => write!(
+                f,
+                "throw {} by the messenger at {} towards {:?} has nothing to throw",
+                throw, messenger, direction
+            ),
+            MoveError::ThrowOffBoard {
+                throw,
+                messenger,
+                direction,
+            } => write!(
+                f,
+                "throw {} by the messenger at {} towards {:?} lands off the board",
+                throw, messenger, direction
+            ),
+            MoveError::CannotThrowPiece { throw, from } => write!(
+                f,
+                "throw {} must throw the player's own cylinder or a messenger, but {} is neither",
+                throw, from
+            ),
+            MoveError::ThrowToOccupied { throw, to } => {
+                write!(f, "throw {} cannot land on {}, it is occupied", throw, to)
+            }
+            MoveError::NoMajority {
+                throw,
+                at,
+                mine,
+                theirs,
+            } => write!(
+                f,
+                "throw {} requires a majority of the player's messengers around {}, \
+                 but there are {} of theirs and {} of their opponent's",
+                throw, at, mine, theirs
+            ),
+            MoveError::ThrowerOffBoard {
+                throw,
+                from,
+                direction,
+            } => write!(
+                f,
+                "throw {} needs a messenger {:?} of {}, which is off the board",
+                throw, direction, from
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Player {
     Beige,
     Black,
@@ -371,197 +725,152 @@ impl Player {
             Player::Black => Player::Beige,
         }
     }
+
+    /// The row this player's cylinder starts on.
+    pub fn home_row(&self) -> i8 {
+        match self {
+            Player::Beige => 7,
+            Player::Black => 1,
+        }
+    }
+
+    /// The row this player's cylinder must reach to win, the opponent's home row.
+    pub fn goal_row(&self) -> i8 {
+        self.opponent().home_row()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Win(Player, Reason),
+    Draw(Reason),
+}
+
+impl Outcome {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            Outcome::Win(player, _) => Some(*player),
+            Outcome::Draw(_) => None,
+        }
+    }
+
+    pub fn reason(&self) -> Reason {
+        match self {
+            Outcome::Win(_, reason) | Outcome::Draw(reason) => *reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Reason {
+    /// A cylinder was thrown onto its owner's goal row.
+    CylinderReachedGoal,
+    /// The player to move has no legal moves. If their opponent has none either the game is a
+    /// draw, otherwise the player to move loses.
+    NoLegalMoves,
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "serialize::GameRepr")
+)]
 pub struct Game {
     to_move: Player,
     board: Board,
+    outcome: Option<Outcome>,
 }
 
 impl Default for Game {
     fn default() -> Self {
-        Game {
-            to_move: Player::Beige,
-            board: STARTING_BOARD,
-        }
+        Game::from_position(STARTING_BOARD, Player::Beige)
     }
 }
 
 impl Game {
     pub fn from_position(position: Board, to_move: Player) -> Game {
-        Game {
+        let mut game = Game {
             to_move,
             board: position,
-        }
+            outcome: None,
+        };
+        game.outcome = game.compute_outcome();
+        game
     }
 
-    pub fn legal_moves(&self) -> Vec<LegalMove> {
-        (1..=7)
-            .flat_map(|y| (1..=7).map(move |x| (x, y)))
-            .flat_map(|(x, y)| BoardCoordinate::new(x, y))
-            .filter(|&coord| {
-                self.board[coord].is_players(self.to_move)
-                    && self.board[coord].is_unstunned_messenger()
-            })
-            .flat_map(|messenger| self.legal_moves_for(messenger))
-            .collect()
+    pub fn to_move(&self) -> Player {
+        self.to_move
     }
 
-    pub fn legal_moves_for(&self, messenger: BoardCoordinate) -> Vec<LegalMove> {
-        if !(self.board[messenger].is_unstunned_messenger()
-            && self.board[messenger].is_players(self.to_move))
-        {
-            return vec![];
-        }
-
-        #[derive(PartialEq, Eq, Hash)]
-        struct SeenThrow {
-            messenger: BoardCoordinate,
-            direction: Direction,
-            throw_from: BoardCoordinate,
-            throw_to: BoardCoordinate,
-        }
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
 
-        impl SeenThrow {
-            fn from_legal_move(legal_move: &LegalMove) -> Self {
-                let move_to = (legal_move.messenger + legal_move.direction).unwrap();
-                let mut throw_to = (move_to + legal_move.first_throw).unwrap();
-                if let Some((second_throw, extra_throws)) = legal_move.extra_throws {
-                    throw_to = ((throw_to + second_throw).unwrap() + second_throw).unwrap();
-                    if let Some((third_throw, extra_throws)) = extra_throws {
-                        throw_to = ((throw_to + third_throw).unwrap() + third_throw).unwrap();
-                        if let Some(fourth_throw) = extra_throws {
-                            throw_to = ((throw_to + fourth_throw).unwrap() + fourth_throw).unwrap();
-                        }
-                    }
-                }
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
 
-                SeenThrow {
-                    messenger: legal_move.messenger,
-                    direction: legal_move.direction,
-                    throw_from: (move_to + -legal_move.first_throw).unwrap(),
-                    throw_to,
-                }
-            }
+    /// The Zobrist hash of the board and the player to move, see [`zobrist`].
+    pub fn zobrist(&self) -> u64 {
+        match self.to_move {
+            Player::Beige => self.board.zobrist(),
+            Player::Black => self.board.zobrist() ^ zobrist::BLACK_TO_MOVE,
         }
+    }
 
-        let mut seen_throws = std::collections::HashSet::new();
-
-        let one_throw = ONE_SQUARE
-            .iter()
-            .chain(TWO_SQUARES.iter())
-            .flat_map(|&direction| {
-                ONE_SQUARE
-                    .iter()
-                    .map(move |&first_throw| (direction, messenger, first_throw))
-            })
-            .flat_map(|(direction, messenger, first_throw)| {
-                LegalMove::from_move(
-                    self,
-                    Move {
-                        direction,
-                        messenger,
-                        first_throw,
-                        player: self.to_move,
-                        extra_throws: None,
-                    },
-                )
-            })
-            .collect::<Vec<LegalMove>>();
-        for legal_move in one_throw.iter() {
-            seen_throws.insert(SeenThrow::from_legal_move(legal_move));
-        }
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
 
-        let two_throws = one_throw
-            .iter()
-            .flat_map(|&legal_move| {
-                ONE_SQUARE
-                    .iter()
-                    .map(move |&second_throw| (legal_move, second_throw))
-            })
-            .flat_map(|(legal_move, second_throw)| {
-                LegalMove::from_move(
-                    self,
-                    Move {
-                        extra_throws: Some((second_throw, None)),
-                        ..legal_move.to_move()
-                    },
-                )
-            })
-            .filter(|legal_move| !seen_throws.contains(&SeenThrow::from_legal_move(legal_move)))
-            .collect::<Vec<LegalMove>>();
-        for legal_move in one_throw.iter() {
-            seen_throws.insert(SeenThrow::from_legal_move(legal_move));
+    fn compute_outcome(&self) -> Option<Outcome> {
+        // the player who just moved is the only one whose cylinder could have been thrown
+        for player in [self.to_move.opponent(), self.to_move] {
+            let reached_goal = (1..=7)
+                .flat_map(|x| BoardCoordinate::new(x, player.goal_row()))
+                .any(|coord| {
+                    self.board[coord].is_cylinder() && self.board[coord].is_players(player)
+                });
+            if reached_goal {
+                return Some(Outcome::Win(player, Reason::CylinderReachedGoal));
+            }
         }
 
-        let three_throws = two_throws
-            .iter()
-            .flat_map(|&legal_move| {
-                ONE_SQUARE
-                    .iter()
-                    .map(move |&third_throw| (legal_move, third_throw))
-            })
-            .flat_map(|(legal_move, third_throw)| {
-                let move_ = legal_move.to_move();
-                let Some((second_throw, None)) = move_.extra_throws else {
-                    // unreachable?
-                    return None;
-                };
-                LegalMove::from_move(
-                    self,
-                    Move {
-                        extra_throws: Some((second_throw, Some((third_throw, None)))),
-                        ..move_
-                    },
-                )
-            })
-            .filter(|legal_move| !seen_throws.contains(&SeenThrow::from_legal_move(legal_move)))
-            .collect::<Vec<LegalMove>>();
-        for legal_move in one_throw.iter() {
-            seen_throws.insert(SeenThrow::from_legal_move(legal_move));
+        if self.has_legal_move() {
+            return None;
         }
 
-        let four_throws = three_throws
-            .iter()
-            .flat_map(|&legal_move| {
-                ONE_SQUARE
-                    .iter()
-                    .map(move |&fourth_throw| (legal_move, fourth_throw))
-            })
-            .flat_map(|(legal_move, fourth_throw)| {
-                let move_ = legal_move.to_move();
-                let Some((second_throw, Some((third_throw, None)))) = move_.extra_throws else {
-                    // unreachable?
-                    return None;
-                };
-                LegalMove::from_move(
-                    self,
-                    Move {
-                        extra_throws: Some((second_throw, Some((third_throw, Some(fourth_throw))))),
-                        ..move_
-                    },
-                )
-            })
-            .filter(|legal_move| !seen_throws.contains(&SeenThrow::from_legal_move(legal_move)))
-            .collect::<Vec<LegalMove>>();
-        for legal_move in one_throw.iter() {
-            seen_throws.insert(SeenThrow::from_legal_move(legal_move));
+        let opponent = Game {
+            to_move: self.to_move.opponent(),
+            ..*self
+        };
+        if opponent.has_legal_move() {
+            Some(Outcome::Win(self.to_move.opponent(), Reason::NoLegalMoves))
+        } else {
+            Some(Outcome::Draw(Reason::NoLegalMoves))
         }
+    }
 
-        let mut moves = one_throw;
-        moves.extend(two_throws);
-        moves.extend(three_throws);
-        moves.extend(four_throws);
-        moves
+    /// Every legal move, see [`MoveOrder::DepthFirst`]. [`Game::moves`] generates them lazily
+    /// and in other orders.
+    pub fn legal_moves(&self) -> Vec<LegalMove> {
+        self.moves(MoveOrder::DepthFirst)
+            .map(|(move_, _)| move_)
+            .collect()
     }
 
-    pub fn make_move(&mut self, move_: &LegalMove) {
-        self.board = self.attempt_move(&move_.0).unwrap().un_stun(self.to_move);
+    /// Play a move, returning the outcome of the game afterwards. Once the game is over no more
+    /// moves are made and [`MoveError::GameOver`] is returned.
+    pub fn make_move(&mut self, move_: &LegalMove) -> Result<Option<Outcome>, MoveError> {
+        self.board = self.board_after(move_)?;
         self.to_move = self.to_move.opponent();
+        self.outcome = self.compute_outcome();
+        Ok(self.outcome)
     }
 
-    pub fn attempt_move(&self, move_: &Move) -> Option<Board> {
+    pub fn attempt_move(&self, move_: &Move) -> Result<Board, MoveError> {
         macro_rules! my_debug {
             ($s:expr $(, $fmt:expr)*) => {
                 #[cfg(feature = "debug")] {
@@ -579,31 +888,41 @@ impl Game {
         }
 
         macro_rules! rule {
-            ($name:expr, $cond:expr $(, $dbg:expr $(, $($fmt:expr),*)?)?) => {
+            ($name:expr, $cond:block else $err:expr $(, $dbg:expr $(, $($fmt:expr),*)?)?) => {
                 $(my_debug!("{}: {}", $name, format!($dbg, $($($fmt),*)?));)?
                 if !$cond {
                     my_error!("rejected move on {:?}: {:#?}\n{:#?}", $name, move_, self.board);
-                    return None;
+                    return Err($err);
                 }
             };
 
-            ($name:expr => $some:expr $(, $dbg:expr $(, $($fmt:expr),*)?)?) => {
+            ($name:expr => $some:block else $err:expr $(, $dbg:expr $(, $($fmt:expr),*)?)?) => {
                 {
                     $(my_debug!("{}: {}", $name, format!($dbg, $($($fmt),*)?));)?
                     match $some {
                         Some(some) => some,
                         _ => {
                             my_error!("rejected move on {:?}: ({:#?})\n{:#?}", $name, move_, self.board);
-                            return None;
+                            return Err($err);
                         }
                     }
                 }
             };
         }
 
+        if let Some(outcome) = self.outcome {
+            my_error!(
+                "rejected move on game over: {:#?}\n{:#?}",
+                move_,
+                self.board
+            );
+            return Err(MoveError::GameOver(outcome));
+        }
+
         rule!(
             "moving player is current player",
-            { self.to_move == move_.player },
+            { self.to_move == move_.player }
+            else MoveError::WrongPlayer { to_move: self.to_move, player: move_.player },
             "to_move={:?} player={:?}",
             self.to_move,
             move_.player
@@ -611,7 +930,8 @@ impl Game {
 
         rule!(
             "messenger to be moved is current player's",
-            { self.board[move_.messenger].is_players(self.to_move) },
+            { self.board[move_.messenger].is_players(self.to_move) }
+            else MoveError::NotPlayersPiece { at: move_.messenger },
             "messenger={} to_move={:?} player={:?}",
             move_.messenger,
             self.to_move,
@@ -620,46 +940,58 @@ impl Game {
 
         rule!(
             "messenger to be moved is not stunned",
-            { self.board[move_.messenger].is_unstunned_messenger() },
+            { self.board[move_.messenger].is_unstunned_messenger() }
+            else if self.board[move_.messenger].is_stunned_messenger() {
+                MoveError::StunnedMessenger { at: move_.messenger }
+            } else {
+                MoveError::NotAMessenger { at: move_.messenger }
+            },
             "messenger={}",
             move_.messenger
         );
 
         let move_to = rule!(
             "messenger is not moving off the board"
-            => { move_.messenger + move_.direction },
+            => { move_.messenger + move_.direction }
+            else MoveError::MoveOffBoard { messenger: move_.messenger, direction: move_.direction },
             "direction={:?}",
             move_.direction
         );
 
         rule!(
             "messenger is moving to an unoccupied square",
-            { self.board[move_to].is_empty() },
+            { self.board[move_to].is_empty() }
+            else MoveError::MoveToOccupied { to: move_to },
             "move_to={}",
             move_to
         );
 
+        let move_through = (move_.messenger + move_.direction.unit()).unwrap();
         rule!(
             "messenger does not hop over occupied squares",
-            { self.board[(move_.messenger + move_.direction.unit()).unwrap()].is_empty() },
+            { self.board[move_through].is_empty() }
+            else MoveError::HopOverOccupied { over: move_through },
             "unit={}",
-            (move_.messenger + move_.direction.unit()).unwrap()
+            move_through
         );
 
-        let after_move = self.board.move_piece(move_.messenger, move_to);
+        let after_move = self
+            .board
+            .move_piece(move_.messenger.index(), move_to.index());
 
         let check_throw = |n: usize, board: Board, messenger: BoardCoordinate, throw: Direction| {
-            let _ = n;
             rule!(
                 "player's messenger is throwing",
-                { board[messenger].is_messenger() && board[messenger].is_players(move_.player) },
+                { board[messenger].is_messenger() && board[messenger].is_players(move_.player) }
+                else MoveError::NotPlayersThrower { throw: n, at: messenger },
                 "messenger={} board[messenger]={:?}",
                 messenger,
                 board[messenger]
             );
             rule!(
                 "throw is unit length",
-                { throw.unit() == throw },
+                { throw.unit() == throw }
+                else MoveError::ThrowNotUnitLength { throw: n, direction: throw },
                 "n={} throw={:?} unit={:?}",
                 n,
                 throw,
@@ -667,7 +999,8 @@ impl Game {
             );
             let throw_from = rule!(
                 "messenger is throwing something on the board"
-                => { messenger + -throw },
+                => { messenger + -throw }
+                else MoveError::ThrowFromOffBoard { throw: n, messenger, direction: throw },
                 "n={} messenger={} throw={:?} -throw={:?}",
                 n,
                 messenger,
@@ -676,7 +1009,8 @@ impl Game {
             );
             let throw_to = rule!(
                 "messenger is throwing onto the board"
-                => { messenger + throw },
+                => { messenger + throw }
+                else MoveError::ThrowOffBoard { throw: n, messenger, direction: throw },
                 "n={} throw={:?}",
                 n,
                 throw
@@ -686,7 +1020,8 @@ impl Game {
                 {
                     (board[throw_from].is_cylinder() && board[throw_from].is_players(move_.player))
                         || board[throw_from].is_messenger()
-                },
+                }
+                else MoveError::CannotThrowPiece { throw: n, from: throw_from },
                 "n={} throw_from={} board[throw_from]={:?}",
                 n,
                 throw_from,
@@ -694,16 +1029,17 @@ impl Game {
             );
             rule!(
                 "throw destination is unoccupied",
-                { board[throw_to].is_empty() },
+                { board[throw_to].is_empty() }
+                else MoveError::ThrowToOccupied { throw: n, to: throw_to },
                 "n={} throw_to={} board[throw_to]={:?}",
                 n,
                 throw_to,
                 board[throw_to]
             );
-            Some((
+            Ok((
                 board
-                    .move_piece(throw_from, throw_to)
-                    .stun_if_opponents(throw_to, move_.player),
+                    .move_piece(throw_from.index(), throw_to.index())
+                    .stun_if_opponents(throw_to.index(), move_.player),
                 throw_to,
             ))
         };
@@ -724,7 +1060,13 @@ impl Game {
                 num_surrounding(after_throw1, move_.player.opponent(), throw_to1);
             rule!(
                 "throw 2 requires a majority of the player's messengers surrounding the destination of throw 1",
-                { my_surrounding > their_surrounding },
+                { my_surrounding > their_surrounding }
+                else MoveError::NoMajority {
+                    throw: 2,
+                    at: throw_to1,
+                    mine: my_surrounding,
+                    theirs: their_surrounding,
+                },
                 "my_surrounding={} their_surrounding={}",
                 my_surrounding,
                 their_surrounding
@@ -732,7 +1074,8 @@ impl Game {
 
             let messenger2 = rule!(
                 "messenger 2 is on the board"
-                => { throw_to1 + throw2 },
+                => { throw_to1 + throw2 }
+                else MoveError::ThrowerOffBoard { throw: 2, from: throw_to1, direction: throw2 },
                 "throw_to1={} throw2={:?}",
                 throw_to1,
                 throw2
@@ -741,7 +1084,7 @@ impl Game {
             let (after_throw2, throw_to2) = check_throw(2, after_throw1, messenger2, throw2)?;
 
             match throw34 {
-                None => Some(after_throw2),
+                None => Ok(after_throw2),
 
                 Some((throw3, None)) => {
                     let my_surrounding = num_surrounding(after_throw2, move_.player, throw_to2);
@@ -749,7 +1092,13 @@ impl Game {
                         num_surrounding(after_throw2, move_.player.opponent(), throw_to2);
                     rule!(
                         "throw 3 requires a majority of the player's messengers surrounding the destination of throw 2",
-                        { my_surrounding > their_surrounding },
+                        { my_surrounding > their_surrounding }
+                        else MoveError::NoMajority {
+                            throw: 3,
+                            at: throw_to2,
+                            mine: my_surrounding,
+                            theirs: their_surrounding,
+                        },
                         "my_surrounding={} their_surrounding={}",
                         my_surrounding,
                         their_surrounding
@@ -757,7 +1106,8 @@ impl Game {
 
                     let messenger3 = rule!(
                         "messenger 3 is on the board"
-                        => { throw_to2 + throw3 },
+                        => { throw_to2 + throw3 }
+                        else MoveError::ThrowerOffBoard { throw: 3, from: throw_to2, direction: throw3 },
                         "throw_to2={} throw3={:?}",
                         throw_to2,
                         throw3
@@ -765,7 +1115,7 @@ impl Game {
 
                     let (after_throw3, _) = check_throw(3, after_throw2, messenger3, throw3)?;
 
-                    Some(after_throw3)
+                    Ok(after_throw3)
                 }
 
                 Some((throw3, Some(throw4))) => {
@@ -774,7 +1124,13 @@ impl Game {
                         num_surrounding(after_throw2, move_.player.opponent(), throw_to2);
                     rule!(
                         "throw 3 requires a majority of the player's messengers surrounding the destination of throw 2",
-                        { my_surrounding > their_surrounding },
+                        { my_surrounding > their_surrounding }
+                        else MoveError::NoMajority {
+                            throw: 3,
+                            at: throw_to2,
+                            mine: my_surrounding,
+                            theirs: their_surrounding,
+                        },
                         "my_surrounding={} their_surrounding={}",
                         my_surrounding,
                         their_surrounding
@@ -782,7 +1138,8 @@ impl Game {
 
                     let messenger3 = rule!(
                         "messenger 3 is on the board"
-                        => { throw_to2 + throw3 },
+                        => { throw_to2 + throw3 }
+                        else MoveError::ThrowerOffBoard { throw: 3, from: throw_to2, direction: throw3 },
                         "throw_to2={} throw3={:?}",
                         throw_to2,
                         throw3
@@ -796,7 +1153,13 @@ impl Game {
                         num_surrounding(after_throw3, move_.player.opponent(), throw_to3);
                     rule!(
                         "throw 4 requires a majority of the player's messengers surrounding the destination of throw 3",
-                        { my_surrounding > their_surrounding },
+                        { my_surrounding > their_surrounding }
+                        else MoveError::NoMajority {
+                            throw: 4,
+                            at: throw_to3,
+                            mine: my_surrounding,
+                            theirs: their_surrounding,
+                        },
                         "my_surrounding={} their_surrounding={}",
                         my_surrounding,
                         their_surrounding
@@ -804,19 +1167,20 @@ impl Game {
 
                     let messenger4 = rule!(
                         "messenger 4 is on the board"
-                        => { throw_to3 + throw3 },
-                        "throw_to3={} throw3={:?}",
+                        => { throw_to3 + throw4 }
+                        else MoveError::ThrowerOffBoard { throw: 4, from: throw_to3, direction: throw4 },
+                        "throw_to3={} throw4={:?}",
                         throw_to3,
-                        throw3
+                        throw4
                     );
 
                     let (after_throw4, _) = check_throw(4, after_throw3, messenger4, throw4)?;
 
-                    Some(after_throw4)
+                    Ok(after_throw4)
                 }
             }
         } else {
-            Some(after_throw1)
+            Ok(after_throw1)
         }
     }
 }
@@ -828,7 +1192,160 @@ mod test {
     fn legal_moves() {
         let game = Game::default();
         for move_ in game.legal_moves() {
-            game.attempt_move(&*move_).unwrap();
+            game.attempt_move(&move_).unwrap();
         }
     }
+
+    #[test]
+    fn set_squares() {
+        let mut board = Game::default().board;
+        let at = BoardCoordinate::new(4, 4).unwrap();
+        board.set(at, Square::Black(Piece::Messenger));
+        assert_eq!(board[at], Square::Black(Piece::Messenger));
+
+        let mut squares = Game::default().board.squares();
+        squares[3][3] = Square::Black(Piece::Messenger);
+        assert_eq!(board, Board::from_squares(squares));
+        assert_eq!(board.zobrist(), Board::from_squares(squares).zobrist());
+    }
+
+    #[test]
+    fn four_throws() {
+        // the fourth messenger is found in the direction of the fourth throw, not the third
+        let game = Game::from_position(BAD_THROW_2, Player::Black);
+        let move_ = Move {
+            player: Player::Black,
+            messenger: BoardCoordinate::new(3, 6).unwrap(),
+            direction: Direction::E,
+            first_throw: Direction::NW,
+            extra_throws: Some((Direction::SE, Some((Direction::W, Some(Direction::NE))))),
+        };
+        assert!(game
+            .legal_moves()
+            .iter()
+            .any(|legal| legal.to_move() == move_));
+        game.attempt_move(&move_).unwrap();
+        for move_ in game.legal_moves() {
+            game.attempt_move(&move_).unwrap();
+        }
+    }
+
+    #[test]
+    fn cylinder_reaching_goal_wins() {
+        #[rustfmt::skip]
+        let board = {
+            use Piece::*;
+            use Square::*;
+
+            Board::from_squares([
+                [Empty, Empty, Empty,            Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Black(Messenger), Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Empty,            Black(Cylinder),  Empty, Empty, Empty],
+                [Empty, Empty, Empty,            Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Empty,            Empty,            Empty, Empty, Beige(Messenger)],
+                [Empty, Empty, Empty,            Beige(Cylinder),  Empty, Empty, Empty],
+                [Empty, Empty, Empty,            Empty,            Empty, Empty, Empty],
+            ])
+        };
+
+        let mut game = Game::from_position(board, Player::Black);
+        assert_eq!(game.outcome(), None);
+
+        let move_ = LegalMove::from_move(
+            &game,
+            Move {
+                player: Player::Black,
+                messenger: BoardCoordinate::new(3, 6).unwrap(),
+                direction: Direction::E,
+                first_throw: Direction::N,
+                extra_throws: None,
+            },
+        )
+        .unwrap();
+        let outcome = Outcome::Win(Player::Black, Reason::CylinderReachedGoal);
+        assert_eq!(game.make_move(&move_), Ok(Some(outcome)));
+        assert!(game.legal_moves().is_empty());
+        assert_eq!(game.make_move(&move_), Err(MoveError::GameOver(outcome)));
+    }
+
+    #[test]
+    fn no_legal_moves_loses() {
+        #[rustfmt::skip]
+        let board = {
+            use Piece::*;
+            use Square::*;
+
+            Board::from_squares([
+                [Empty, Empty, Empty, Beige(Cylinder),  Empty, Empty, Empty],
+                [Empty, Empty, Empty, Beige(Messenger), Empty, Empty, Empty],
+                [Empty, Empty, Empty, Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Empty, Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Empty, Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Empty, Empty,            Empty, Empty, Empty],
+                [Empty, Empty, Empty, Black(Cylinder),  Empty, Empty, Empty],
+            ])
+        };
+
+        let game = Game::from_position(board, Player::Black);
+        assert_eq!(
+            game.outcome(),
+            Some(Outcome::Win(Player::Beige, Reason::NoLegalMoves))
+        );
+    }
+
+    #[test]
+    fn rejection_reasons() {
+        let game = Game::default();
+        let move_ = Move {
+            player: Player::Beige,
+            messenger: BoardCoordinate::new(4, 5).unwrap(),
+            direction: Direction::S,
+            first_throw: Direction::N,
+            extra_throws: None,
+        };
+
+        assert_eq!(
+            game.attempt_move(&Move {
+                player: Player::Black,
+                ..move_
+            })
+            .unwrap_err(),
+            MoveError::WrongPlayer {
+                to_move: Player::Beige,
+                player: Player::Black
+            }
+        );
+        assert_eq!(
+            game.attempt_move(&Move {
+                direction: Direction::N,
+                ..move_
+            })
+            .unwrap_err(),
+            MoveError::MoveToOccupied {
+                to: BoardCoordinate::new(4, 6).unwrap()
+            }
+        );
+        assert_eq!(
+            game.attempt_move(&Move {
+                first_throw: Direction::N2,
+                ..move_
+            })
+            .unwrap_err(),
+            MoveError::ThrowNotUnitLength {
+                throw: 1,
+                direction: Direction::N2
+            }
+        );
+        assert_eq!(
+            game.attempt_move(&Move {
+                messenger: BoardCoordinate::new(4, 6).unwrap(),
+                direction: Direction::S2,
+                ..move_
+            })
+            .unwrap_err(),
+            MoveError::HopOverOccupied {
+                over: BoardCoordinate::new(4, 5).unwrap()
+            }
+        );
+    }
 }
@@ -1,8 +1,243 @@
-use cotw::{BoardCoordinate, Direction, Game, LegalMove, Move, Player};
+use cotw::{
+    tournament::Contestant, AlphaBeta, BoardCoordinate, BookBuilder, Direction, EngineProcess,
+    Game, GameRecord, LegalMove, Match, MatchConfig, Move, Player, SearchLimits, Sprt, Table,
+    Tablebase,
+};
 
 fn main() {
     tracing_subscriber::fmt::init();
 
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("perft") => perft(&args[1..]),
+        Some("book") => book(&args[1..]),
+        Some("tablebase") => tablebase(&args[1..]),
+        Some("match") => match_(&args[1..]),
+        _ => demo(),
+    }
+}
+
+/// `cotw perft <depth> [position]`, counting the moves from the position, or the starting
+/// position.
+fn perft(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!("usage: cotw perft <depth> [position]");
+        std::process::exit(1);
+    };
+    let Some(Ok(depth)) = args.first().map(|depth| depth.parse::<u32>()) else {
+        usage();
+    };
+    let game = match &args[1..] {
+        [] => Game::default(),
+        position => match Game::from_position_string(&position.join(" ")) {
+            Ok(game) => game,
+            Err(err) => {
+                eprintln!("invalid position: {err}");
+                usage();
+            }
+        },
+    };
+
+    let start = std::time::Instant::now();
+    let mut total = 0;
+    for (move_, nodes) in game.perft_divide(depth) {
+        println!("{move_}: {nodes}");
+        total += nodes;
+    }
+    if depth == 0 {
+        total = 1;
+    }
+    println!();
+    println!("nodes: {total}");
+    println!("time: {:.2?}", start.elapsed());
+}
+
+/// `cotw book <max plies> <record files...>`, printing an opening book built from the records.
+fn book(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!("usage: cotw book <max plies> <record files...>");
+        std::process::exit(1);
+    };
+    let Some(Ok(max_plies)) = args.first().map(|plies| plies.parse::<usize>()) else {
+        usage();
+    };
+    if args.len() < 2 {
+        usage();
+    }
+
+    let mut builder = BookBuilder::new(max_plies);
+    let mut games = 0;
+    for path in args[1..].iter() {
+        let records = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|s| GameRecord::parse_all(&s).map_err(|err| err.to_string()));
+        let records = match records {
+            Ok(records) => records,
+            Err(err) => {
+                eprintln!("{path}: {err}");
+                std::process::exit(1);
+            }
+        };
+        for (i, record) in records.iter().enumerate() {
+            match builder.add_record(record) {
+                Ok(()) => games += 1,
+                Err(err) => eprintln!("{path}: skipping game {}: {err}", i + 1),
+            }
+        }
+    }
+
+    println!("# {games} games, {max_plies} plies");
+    print!("{}", builder.build());
+}
+
+/// `cotw tablebase <max messengers> <file>`, solving every endgame with up to that many messengers
+/// for each player. Tables too big to solve are skipped, and the rest are still written.
+fn tablebase(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!("usage: cotw tablebase <max messengers> <file>");
+        std::process::exit(1);
+    };
+    let [max_messengers, path] = args else {
+        usage();
+    };
+    let Ok(max_messengers) = max_messengers.parse::<usize>() else {
+        usage();
+    };
+
+    let mut tablebase = Tablebase::default();
+    let mut skipped = false;
+    for beige in 0..=max_messengers {
+        for black in 0..=max_messengers {
+            let start = std::time::Instant::now();
+            match Table::generate(beige, black) {
+                Ok(table) => {
+                    tablebase.insert(table);
+                    println!("{beige} vs {black} messengers: {:.2?}", start.elapsed());
+                }
+                Err(err) => {
+                    eprintln!("skipping {beige} vs {black} messengers: {err}");
+                    skipped = true;
+                }
+            }
+        }
+    }
+
+    let written = std::fs::File::create(path)
+        .map(std::io::BufWriter::new)
+        .and_then(|file| tablebase.write(file));
+    if let Err(err) = written {
+        eprintln!("{path}: {err}");
+        std::process::exit(1);
+    }
+    if skipped {
+        std::process::exit(1);
+    }
+}
+
+/// `cotw match [options] <records file> <engine> <engine>`, playing one engine against another and
+/// writing every game to the records file. Engines are either `alphabeta` for the built-in search,
+/// or a command that starts an engine speaking the protocol.
+fn match_(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!(
+            "usage: cotw match [--games <n>] [--depth <plies>] [--movetime <ms>] \
+             [--max-plies <plies>] [--openings <file>] [--sprt <elo0> <elo1>] \
+             <records file> <engine> <engine>"
+        );
+        std::process::exit(1);
+    };
+    fn value<T: std::str::FromStr>(args: &mut impl Iterator<Item = String>) -> Option<T> {
+        args.next().and_then(|value| value.parse().ok())
+    }
+
+    let mut config = MatchConfig::default();
+    let mut limits = SearchLimits::default();
+    let mut positional = Vec::new();
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => config.games = value(&mut args).unwrap_or_else(|| usage()),
+            "--depth" => limits.depth = Some(value(&mut args).unwrap_or_else(|| usage())),
+            "--movetime" => {
+                let ms = value(&mut args).unwrap_or_else(|| usage());
+                limits.time = Some(std::time::Duration::from_millis(ms));
+            }
+            "--max-plies" => config.max_plies = value(&mut args).unwrap_or_else(|| usage()),
+            "--openings" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                let openings = std::fs::read_to_string(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|s| {
+                        s.lines()
+                            .map(str::trim)
+                            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                            .map(|line| Game::from_position_string(line).map_err(|e| e.to_string()))
+                            .collect::<Result<Vec<_>, _>>()
+                    });
+                config.openings = openings.unwrap_or_else(|err| {
+                    eprintln!("{path}: {err}");
+                    std::process::exit(1);
+                });
+            }
+            "--sprt" => {
+                let elo0 = value(&mut args).unwrap_or_else(|| usage());
+                let elo1 = value(&mut args).unwrap_or_else(|| usage());
+                config.sprt = Some(Sprt::new(elo0, elo1));
+            }
+            _ if arg.starts_with("--") => usage(),
+            _ => positional.push(arg),
+        }
+    }
+    let [path, first, second] = &positional[..] else {
+        usage();
+    };
+    if limits.depth.is_none() && limits.time.is_none() {
+        limits.depth = Some(3);
+    }
+
+    let contestant = |command: &str| -> Contestant {
+        if command == "alphabeta" {
+            return Contestant::new(command, AlphaBeta::new(limits));
+        }
+        let mut words = command.split_whitespace();
+        let mut process = std::process::Command::new(words.next().unwrap_or_else(|| usage()));
+        match EngineProcess::spawn(process.args(words)) {
+            Ok(mut engine) => {
+                engine.limits = limits;
+                Contestant::new(engine.name().to_string(), engine)
+            }
+            Err(err) => {
+                eprintln!("{command}: {err}");
+                std::process::exit(1);
+            }
+        }
+    };
+    let (mut first, mut second) = (contestant(first), contestant(second));
+    // records need to tell the engines apart, which two copies of one engine don't
+    if first.name == second.name {
+        first.name.push_str(" #1");
+        second.name.push_str(" #2");
+    }
+    let mut match_ = Match::new(config, first, second);
+
+    let mut records = std::fs::File::create(path)
+        .map(std::io::BufWriter::new)
+        .unwrap_or_else(|err| {
+            eprintln!("{path}: {err}");
+            std::process::exit(1);
+        });
+    let results = match_.play(|record, results| {
+        use std::io::Write;
+        if let Err(err) = writeln!(records, "{record}").and_then(|_| records.flush()) {
+            eprintln!("{path}: {err}");
+            std::process::exit(1);
+        }
+        println!("game {}: {} ({})", results.games(), record.result, results);
+    });
+    println!("{} vs {}: {results}", match_.first.name, match_.second.name);
+}
+
+fn demo() {
     let mut game = Game::default();
     dbg!(&game);
     game.make_move(
@@ -17,7 +252,8 @@ fn main() {
             },
         )
         .unwrap(),
-    );
+    )
+    .unwrap();
     dbg!(&game);
     game.make_move(
         &LegalMove::from_move(
@@ -31,7 +267,8 @@ fn main() {
             },
         )
         .unwrap(),
-    );
+    )
+    .unwrap();
     dbg!(&game);
     game.make_move(
         &LegalMove::from_move(
@@ -45,7 +282,8 @@ fn main() {
             },
         )
         .unwrap(),
-    );
+    )
+    .unwrap();
     dbg!(&game);
     game.make_move(
         &LegalMove::from_move(
@@ -59,7 +297,8 @@ fn main() {
             },
         )
         .unwrap(),
-    );
+    )
+    .unwrap();
     dbg!(&game);
 
     //    let move_ = LegalMove::from_move(
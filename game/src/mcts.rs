@@ -0,0 +1,191 @@
+//! Monte Carlo tree search, using UCT to pick which moves to explore and uniformly random
+//! playouts to score them.
+
+use crate::{engine::Engine, rng::Rng, Game, LegalMove, MoveGroup, Player};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// How many playouts to run per move.
+    pub iterations: u32,
+    /// Larger values explore less promising moves more often.
+    pub exploration: f64,
+    /// Playouts still going after this many plies are scored as draws.
+    pub max_playout_plies: u32,
+    pub seed: u64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            iterations: 1000,
+            exploration: std::f64::consts::SQRT_2,
+            max_playout_plies: 200,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MctsResult {
+    /// `None` if the game is already over.
+    pub best_move: Option<LegalMove>,
+    /// How many playouts went through the best move.
+    pub visits: u32,
+    /// The fraction of those playouts the player to move won, counting draws as half.
+    pub win_rate: f64,
+}
+
+struct Node {
+    game: Game,
+    move_: Option<LegalMove>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<LegalMove>,
+    visits: u32,
+    /// The total score of the playouts through this node, for the player who made `move_`.
+    score: f64,
+}
+
+impl Node {
+    fn new(game: Game, move_: Option<LegalMove>, parent: Option<usize>) -> Node {
+        Node {
+            untried: game
+                .distinct_moves()
+                .iter()
+                .map(MoveGroup::representative)
+                .collect(),
+            game,
+            move_,
+            parent,
+            children: Vec::new(),
+            visits: 0,
+            score: 0.0,
+        }
+    }
+}
+
+pub struct Mcts {
+    pub config: MctsConfig,
+    rng: Rng,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Mcts {
+        Mcts {
+            rng: Rng::new(config.seed),
+            config,
+        }
+    }
+
+    pub fn search(&mut self, game: &Game) -> MctsResult {
+        let mut nodes = vec![Node::new(*game, None, None)];
+
+        for _ in 0..self.config.iterations {
+            if nodes[0].untried.is_empty() && nodes[0].children.is_empty() {
+                break;
+            }
+
+            let mut current = 0;
+            while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+                current = self.select(&nodes, current);
+            }
+
+            if !nodes[current].untried.is_empty() {
+                let i = self.rng.below(nodes[current].untried.len());
+                let move_ = nodes[current].untried.swap_remove(i);
+                let mut child = nodes[current].game;
+                child.make_move(&move_).expect("legal move is legal");
+                nodes.push(Node::new(child, Some(move_), Some(current)));
+                let child = nodes.len() - 1;
+                nodes[current].children.push(child);
+                current = child;
+            }
+
+            let winner = self.playout(nodes[current].game);
+
+            let mut node = Some(current);
+            while let Some(i) = node {
+                let mover = nodes[i].game.to_move().opponent();
+                nodes[i].visits += 1;
+                nodes[i].score += match winner {
+                    Some(winner) if winner == mover => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                node = nodes[i].parent;
+            }
+        }
+
+        let best = nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| nodes[child].visits)
+            .map(|&child| &nodes[child]);
+        MctsResult {
+            best_move: best.and_then(|node| node.move_),
+            visits: best.map(|node| node.visits).unwrap_or(0),
+            win_rate: best
+                .map(|node| node.score / node.visits as f64)
+                .unwrap_or(0.0),
+        }
+    }
+
+    fn select(&self, nodes: &[Node], parent: usize) -> usize {
+        let ln_visits = (nodes[parent].visits as f64).ln();
+        let uct = |child: usize| {
+            let node = &nodes[child];
+            let visits = node.visits as f64;
+            node.score / visits + self.config.exploration * (ln_visits / visits).sqrt()
+        };
+        *nodes[parent]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .unwrap()
+    }
+
+    /// Play random moves until the game ends, returning the winner.
+    fn playout(&mut self, mut game: Game) -> Option<Player> {
+        for _ in 0..self.config.max_playout_plies {
+            if let Some(outcome) = game.outcome() {
+                return outcome.winner();
+            }
+            let moves = game.legal_moves();
+            let move_ = moves[self.rng.below(moves.len())];
+            game.make_move(&move_).expect("legal move is legal");
+        }
+        game.outcome().and_then(|outcome| outcome.winner())
+    }
+}
+
+impl Engine for Mcts {
+    fn choose_move(&mut self, game: &Game) -> Option<LegalMove> {
+        self.search(game).best_move
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_win() {
+        let game = Game::from_position_string("7/2m4/3c3/7/6M/3C3/7 black").unwrap();
+        let config = MctsConfig {
+            iterations: 300,
+            max_playout_plies: 20,
+            seed: 7,
+            ..MctsConfig::default()
+        };
+
+        let result = Mcts::new(config).search(&game);
+        let mut after = game;
+        let outcome = after.make_move(&result.best_move.unwrap()).unwrap();
+        assert_eq!(outcome.unwrap().winner(), Some(Player::Black));
+        assert!(result.win_rate > 0.99);
+
+        let again = Mcts::new(config).search(&game);
+        assert_eq!(again.best_move, result.best_move);
+        assert_eq!(again.visits, result.visits);
+    }
+}
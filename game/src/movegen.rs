@@ -0,0 +1,513 @@
+//! Legal move generation. Moves are built up a throw at a time on the board's masks, so each
+//! candidate is only checked against the throws that came before it instead of being validated
+//! from scratch with [`Game::attempt_move`].
+
+use std::collections::{hash_map::Entry, VecDeque};
+
+use crate::{
+    Board, BoardCoordinate, Direction, Game, LegalMove, Mask, Move, MoveError, Player, Square,
+    ONE_SQUARE, TWO_SQUARES,
+};
+
+const OFF_BOARD: u8 = u8::MAX;
+
+const fn offset(direction: Direction) -> (i8, i8) {
+    use Direction::*;
+    match direction {
+        NW2 => (-2, 2),
+        N2 => (0, 2),
+        NE2 => (2, 2),
+        NW => (-1, 1),
+        N => (0, 1),
+        NE => (1, 1),
+        W2 => (-2, 0),
+        W => (-1, 0),
+        E => (1, 0),
+        E2 => (2, 0),
+        SW => (-1, -1),
+        S => (0, -1),
+        SE => (1, -1),
+        SW2 => (-2, -2),
+        S2 => (0, -2),
+        SE2 => (2, -2),
+    }
+}
+
+/// The square in each direction from each square, indexed by bit then `Direction as usize`.
+const TARGETS: [[u8; 16]; 49] = {
+    let mut targets = [[OFF_BOARD; 16]; 49];
+    let mut index = 0;
+    while index < 49 {
+        let (x, y) = ((index % 7) as i8 + 1, 7 - (index / 7) as i8);
+        let mut i = 0;
+        while i < 16 {
+            let direction = if i < 8 {
+                ONE_SQUARE[i]
+            } else {
+                TWO_SQUARES[i - 8]
+            };
+            let (dx, dy) = offset(direction);
+            let (x, y) = (x + dx, y + dy);
+            if 1 <= x && x <= 7 && 1 <= y && y <= 7 {
+                targets[index][direction as usize] = ((7 - y) * 7 + x - 1) as u8;
+            }
+            i += 1;
+        }
+        index += 1;
+    }
+    targets
+};
+
+/// The squares one away from each square.
+const NEIGHBORS: [Mask; 49] = {
+    let mut neighbors = [0; 49];
+    let mut index = 0;
+    while index < 49 {
+        let mut i = 0;
+        while i < 8 {
+            let target = TARGETS[index][ONE_SQUARE[i] as usize];
+            if target != OFF_BOARD {
+                neighbors[index] |= 1 << target;
+            }
+            i += 1;
+        }
+        index += 1;
+    }
+    neighbors
+};
+
+fn target(from: usize, direction: Direction) -> Option<usize> {
+    match TARGETS[from][direction as usize] {
+        OFF_BOARD => None,
+        target => Some(target as usize),
+    }
+}
+
+/// A legal move so far, and the board after its last throw.
+#[derive(Clone, Copy)]
+struct Partial {
+    move_: Move,
+    board: Board,
+    first_landing: usize,
+    landing: usize,
+}
+
+/// The piece behind `thrower` thrown over it, if that's allowed.
+fn throw(
+    board: &Board,
+    player: Player,
+    thrower: usize,
+    direction: Direction,
+) -> Option<(Board, usize)> {
+    let from = target(thrower, -direction)?;
+    let to = target(thrower, direction)?;
+    let cylinder = match player {
+        Player::Beige => Square::Beige(crate::Piece::Cylinder),
+        Player::Black => Square::Black(crate::Piece::Cylinder),
+    };
+    let throwable =
+        board.pieces(cylinder) | board.messengers(Player::Beige) | board.messengers(Player::Black);
+    if throwable & (1 << from) == 0 || board.occupied() & (1 << to) != 0 {
+        return None;
+    }
+    Some((board.move_piece(from, to).stun_if_opponents(to, player), to))
+}
+
+/// Moves of `messenger` with only their first throw.
+fn single_throws(game: &Game, messenger: usize) -> impl Iterator<Item = Partial> + '_ {
+    let player = game.to_move;
+    let occupied = game.board.occupied();
+    ONE_SQUARE
+        .iter()
+        .chain(TWO_SQUARES.iter())
+        .filter_map(move |&direction| {
+            let to = target(messenger, direction)?;
+            let through = target(messenger, direction.unit())?;
+            if occupied & (1 << to | 1 << through) != 0 {
+                return None;
+            }
+            Some((direction, to, game.board.move_piece(messenger, to)))
+        })
+        .flat_map(move |(direction, to, after_move)| {
+            ONE_SQUARE.iter().filter_map(move |&first_throw| {
+                let (board, landing) = throw(&after_move, player, to, first_throw)?;
+                Some(Partial {
+                    move_: Move {
+                        player,
+                        messenger: BoardCoordinate::from_index(messenger),
+                        direction,
+                        first_throw,
+                        extra_throws: None,
+                    },
+                    board,
+                    first_landing: landing,
+                    landing,
+                })
+            })
+        })
+}
+
+/// `partial` with another throw by the messenger in `direction` from where the piece landed.
+fn extra_throws(partial: Partial) -> impl Iterator<Item = Partial> {
+    let player = partial.move_.player;
+    let neighbors = NEIGHBORS[partial.landing];
+    let mine = (partial.board.messengers(player) & neighbors).count_ones();
+    let theirs = (partial.board.messengers(player.opponent()) & neighbors).count_ones();
+    let majority = mine > theirs;
+
+    ONE_SQUARE.iter().filter_map(move |&direction| {
+        if !majority {
+            return None;
+        }
+        let thrower = target(partial.landing, direction)?;
+        if partial.board.messengers(player) & (1 << thrower) == 0 {
+            return None;
+        }
+        let (board, landing) = throw(&partial.board, player, thrower, direction)?;
+        let extra_throws = match partial.move_.extra_throws {
+            None => Some((direction, None)),
+            Some((second, None)) => Some((second, Some((direction, None)))),
+            Some((second, Some((third, None)))) => Some((second, Some((third, Some(direction))))),
+            Some((_, Some((_, Some(_))))) => return None,
+        };
+        Some(Partial {
+            move_: Move {
+                extra_throws,
+                ..partial.move_
+            },
+            board,
+            landing,
+            ..partial
+        })
+    })
+}
+
+impl Game {
+    pub(crate) fn has_legal_move(&self) -> bool {
+        // every legal move with extra throws is still legal with only its first throw
+        let mut messengers = self.board.pieces(match self.to_move {
+            Player::Beige => Square::Beige(crate::Piece::Messenger),
+            Player::Black => Square::Black(crate::Piece::Messenger),
+        });
+        while messengers != 0 {
+            if single_throws(self, messengers.trailing_zeros() as usize)
+                .next()
+                .is_some()
+            {
+                return true;
+            }
+            messengers &= messengers - 1;
+        }
+        false
+    }
+
+    /// The legal moves with the board after each, generated as they're asked for.
+    pub fn moves(&self, order: MoveOrder) -> Moves<'_> {
+        let messengers = (1..=7)
+            .flat_map(|y| (1..=7).map(move |x| (x, y)))
+            .flat_map(|(x, y)| BoardCoordinate::new(x, y))
+            .filter(|&messenger| {
+                self.board[messenger].is_unstunned_messenger()
+                    && self.board[messenger].is_players(self.to_move)
+            })
+            .collect();
+        Moves::new(self, messengers, order)
+    }
+
+    /// [`Game::moves`] of only `messenger`.
+    pub fn moves_for(&self, messenger: BoardCoordinate, order: MoveOrder) -> Moves<'_> {
+        let messengers = if self.board[messenger].is_unstunned_messenger()
+            && self.board[messenger].is_players(self.to_move)
+        {
+            vec![messenger]
+        } else {
+            vec![]
+        };
+        Moves::new(self, messengers, order)
+    }
+
+    /// Every legal move of `messenger`. Each move is followed by the moves that add throws to it.
+    pub fn legal_moves_for(&self, messenger: BoardCoordinate) -> Vec<LegalMove> {
+        self.moves_for(messenger, MoveOrder::DepthFirst)
+            .map(|(move_, _)| move_)
+            .collect()
+    }
+
+    /// Every legal move of `messenger` with the board after it, in the order of
+    /// [`Game::legal_moves_for`].
+    pub fn legal_moves_with_boards_for(
+        &self,
+        messenger: BoardCoordinate,
+    ) -> Vec<(LegalMove, Board)> {
+        self.moves_for(messenger, MoveOrder::DepthFirst).collect()
+    }
+
+    /// Every legal move with the board after it, in the order of [`Game::legal_moves`].
+    pub fn legal_moves_with_boards(&self) -> Vec<(LegalMove, Board)> {
+        self.moves(MoveOrder::DepthFirst).collect()
+    }
+
+    /// The board after `move_`, as if by [`Game::make_move`].
+    pub fn board_after(&self, move_: &Move) -> Result<Board, MoveError> {
+        Ok(self.attempt_move(move_)?.un_stun(self.to_move))
+    }
+
+    /// The game after a move that leaves `board`, as if by [`Game::make_move`].
+    pub fn after(&self, board: Board) -> Game {
+        Game::from_position(board, self.to_move.opponent())
+    }
+
+    /// The legal moves grouped by the position they leave, in the order of each group's first
+    /// move in [`Game::legal_moves`].
+    pub fn distinct_moves(&self) -> Vec<MoveGroup> {
+        let mut groups = Vec::<MoveGroup>::new();
+        let mut index = std::collections::HashMap::<Board, usize>::new();
+        for (move_, board) in self.legal_moves_with_boards() {
+            match index.entry(board) {
+                Entry::Occupied(entry) => groups[*entry.get()].moves.push(move_),
+                Entry::Vacant(entry) => {
+                    entry.insert(groups.len());
+                    groups.push(MoveGroup {
+                        board,
+                        moves: vec![move_],
+                    });
+                }
+            }
+        }
+        for group in groups.iter_mut() {
+            group.moves.sort_by_key(|move_| throws(move_));
+        }
+        groups
+    }
+}
+
+/// The order [`Moves`] generates moves in. Orders that put good moves first let a search stop
+/// before generating the rest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MoveOrder {
+    /// One messenger at a time, with each move followed by the moves that add throws to it.
+    #[default]
+    DepthFirst,
+    /// Every move with one throw, then every move with two, and so on.
+    FewestThrows,
+    /// Moves that stun an opponent's messenger, then the rest, each depth first.
+    StunsFirst,
+}
+
+/// An iterator over legal moves and the boards after them, see [`Game::moves`].
+pub struct Moves<'game> {
+    game: &'game Game,
+    order: MoveOrder,
+    /// Messengers whose moves haven't been started, the next one last.
+    messengers: Vec<BoardCoordinate>,
+    /// Moves that haven't been yielded or extended with more throws yet.
+    pending: VecDeque<Partial>,
+    /// Moves that don't stun anything, held back until the ones that do are done.
+    deferred: VecDeque<Partial>,
+}
+
+impl<'game> Moves<'game> {
+    fn new(game: &'game Game, mut messengers: Vec<BoardCoordinate>, order: MoveOrder) -> Self {
+        if game.is_over() {
+            messengers.clear();
+        }
+        messengers.reverse();
+        let mut moves = Moves {
+            game,
+            order,
+            messengers,
+            pending: VecDeque::new(),
+            deferred: VecDeque::new(),
+        };
+        if order == MoveOrder::FewestThrows {
+            while let Some(messenger) = moves.messengers.pop() {
+                moves.pending.extend(single_throws(game, messenger.index()));
+            }
+        }
+        moves
+    }
+
+    fn stuns(&self, partial: &Partial) -> bool {
+        let stunned = match self.game.to_move {
+            Player::Beige => Square::Black(crate::Piece::StunnedMessenger),
+            Player::Black => Square::Beige(crate::Piece::StunnedMessenger),
+        };
+        partial.board.pieces(stunned).count_ones() > self.game.board.pieces(stunned).count_ones()
+    }
+}
+
+impl Iterator for Moves<'_> {
+    type Item = (LegalMove, Board);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let next = match self.order {
+                MoveOrder::FewestThrows => self.pending.pop_front(),
+                MoveOrder::DepthFirst | MoveOrder::StunsFirst => self.pending.pop_back(),
+            };
+
+            let Some(partial) = next else {
+                if let Some(messenger) = self.messengers.pop() {
+                    let single_throws = single_throws(self.game, messenger.index());
+                    let start = self.pending.len();
+                    self.pending.extend(single_throws);
+                    self.pending.make_contiguous()[start..].reverse();
+                    continue;
+                }
+                let partial = self.deferred.pop_front()?;
+                return Some((
+                    LegalMove(partial.move_),
+                    partial.board.un_stun(self.game.to_move),
+                ));
+            };
+
+            // a chain that puts the piece back where the first throw did isn't continued
+            let extended = extra_throws(partial).filter(|next| next.landing != next.first_landing);
+            match self.order {
+                MoveOrder::FewestThrows => self.pending.extend(extended),
+                MoveOrder::DepthFirst | MoveOrder::StunsFirst => {
+                    let start = self.pending.len();
+                    self.pending.extend(extended);
+                    self.pending.make_contiguous()[start..].reverse();
+                }
+            }
+
+            if self.order == MoveOrder::StunsFirst && !self.stuns(&partial) {
+                self.deferred.push_back(partial);
+                continue;
+            }
+            return Some((
+                LegalMove(partial.move_),
+                partial.board.un_stun(self.game.to_move),
+            ));
+        }
+    }
+}
+
+/// How many throws `move_` makes.
+pub(crate) fn throws(move_: &Move) -> usize {
+    match move_.extra_throws {
+        None => 1,
+        Some((_, None)) => 2,
+        Some((_, Some((_, None)))) => 3,
+        Some((_, Some((_, Some(_))))) => 4,
+    }
+}
+
+/// Legal moves which all leave the same board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveGroup {
+    pub board: Board,
+    /// In the order they're generated, except that moves with fewer throws come first.
+    pub moves: Vec<LegalMove>,
+}
+
+impl MoveGroup {
+    /// The move to show or search for the whole group.
+    pub fn representative(&self) -> LegalMove {
+        self.moves[0]
+    }
+
+    /// The other moves that leave the same board.
+    pub fn equivalent(&self) -> &[LegalMove] {
+        &self.moves[1..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn agrees_with_attempt_move() {
+        let mut rng = crate::rng::Rng::new(16);
+        for _ in 0..4 {
+            let mut game = Game::default();
+            while !game.is_over() {
+                let mut messengers = game.board.pieces(match game.to_move {
+                    Player::Beige => Square::Beige(crate::Piece::Messenger),
+                    Player::Black => Square::Black(crate::Piece::Messenger),
+                });
+                while messengers != 0 {
+                    let mut throws = single_throws(&game, messengers.trailing_zeros() as usize)
+                        .collect::<Vec<_>>();
+                    while !throws.is_empty() {
+                        for partial in throws.iter() {
+                            assert_eq!(game.attempt_move(&partial.move_), Ok(partial.board));
+                        }
+                        throws = throws.into_iter().flat_map(extra_throws).collect();
+                    }
+                    messengers &= messengers - 1;
+                }
+
+                let moves = game.legal_moves();
+                game.make_move(&moves[rng.below(moves.len())]).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn moves_with_boards() {
+        let game = Game::from_position(crate::BAD_THROW_2, Player::Black);
+        let moves = game.legal_moves_with_boards();
+        assert_eq!(
+            moves.iter().map(|(move_, _)| *move_).collect::<Vec<_>>(),
+            game.legal_moves()
+        );
+        for (move_, board) in moves {
+            let mut after = game;
+            after.make_move(&move_).unwrap();
+            assert_eq!(after.board(), &board);
+            assert_eq!(game.after(board).zobrist(), after.zobrist());
+        }
+    }
+
+    #[test]
+    fn orders() {
+        let game = Game::from_position(crate::BAD_THROW_3, Player::Black);
+        let moves = game.legal_moves_with_boards();
+        let stunned = |board: &Board| {
+            board
+                .pieces(Square::Beige(crate::Piece::StunnedMessenger))
+                .count_ones()
+        };
+
+        for order in [MoveOrder::FewestThrows, MoveOrder::StunsFirst] {
+            let ordered = game.moves(order).collect::<Vec<_>>();
+            assert_eq!(ordered.len(), moves.len());
+            assert!(moves.iter().all(|move_| ordered.contains(move_)));
+
+            let sorted = match order {
+                MoveOrder::FewestThrows => ordered.is_sorted_by_key(|(move_, _)| throws(move_)),
+                _ => ordered.is_sorted_by_key(|(_, board)| stunned(board) == 0),
+            };
+            assert!(sorted, "{order:?}");
+        }
+        assert!(stunned(&game.moves(MoveOrder::StunsFirst).next().unwrap().1) > 0);
+    }
+
+    #[test]
+    fn distinct_moves() {
+        let game = Game::from_position(crate::BAD_THROW_3, Player::Black);
+        let moves = game.legal_moves();
+        let groups = game.distinct_moves();
+        assert_eq!(
+            groups.iter().map(|group| group.moves.len()).sum::<usize>(),
+            moves.len()
+        );
+        assert!(groups.len() < moves.len());
+
+        for (i, group) in groups.iter().enumerate() {
+            assert!(groups[i + 1..]
+                .iter()
+                .all(|other| other.board != group.board));
+            for move_ in group.moves.iter() {
+                let mut after = game;
+                after.make_move(move_).unwrap();
+                assert_eq!(*after.board(), group.board);
+            }
+            let fewest = group.moves.iter().map(|move_| throws(move_)).min().unwrap();
+            assert_eq!(throws(&group.representative()), fewest);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Move notation, written as the messenger's square, the direction it moves in, and each of its
+//! throws. For example `d5-S/N,NE` moves the messenger on d5 one square south, throws north, then
+//! has the messenger to the northeast of the thrown piece throw it again.
+
+use crate::{BoardCoordinate, Direction, ExtraThrows, Game, LegalMove, Move, MoveError, Player};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveNotation {
+    pub messenger: BoardCoordinate,
+    pub direction: Direction,
+    pub first_throw: Direction,
+    pub extra_throws: ExtraThrows,
+}
+
+impl MoveNotation {
+    pub fn for_player(self, player: Player) -> Move {
+        Move {
+            player,
+            messenger: self.messenger,
+            direction: self.direction,
+            first_throw: self.first_throw,
+            extra_throws: self.extra_throws,
+        }
+    }
+}
+
+impl From<Move> for MoveNotation {
+    fn from(move_: Move) -> Self {
+        MoveNotation {
+            messenger: move_.messenger,
+            direction: move_.direction,
+            first_throw: move_.first_throw,
+            extra_throws: move_.extra_throws,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    InvalidCoordinate(String),
+    InvalidDirection(String),
+    MissingDirection,
+    MissingThrow,
+    TooManyThrows(usize),
+    IllegalMove(MoveError),
+}
+
+impl std::fmt::Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotationError::InvalidCoordinate(s) => write!(f, "{:?} is not a square", s),
+            NotationError::InvalidDirection(s) => write!(f, "{:?} is not a direction", s),
+            NotationError::MissingDirection => write!(f, "expected '-' and a direction"),
+            NotationError::MissingThrow => write!(f, "expected '/' and a throw"),
+            NotationError::TooManyThrows(n) => write!(f, "{} throws given, at most 4 allowed", n),
+            NotationError::IllegalMove(err) => write!(f, "illegal move: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+impl From<MoveError> for NotationError {
+    fn from(err: MoveError) -> Self {
+        NotationError::IllegalMove(err)
+    }
+}
+
+impl std::fmt::Display for MoveNotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}/{}",
+            self.messenger, self.direction, self.first_throw
+        )?;
+        if let Some((second, rest)) = self.extra_throws {
+            write!(f, ",{}", second)?;
+            if let Some((third, fourth)) = rest {
+                write!(f, ",{}", third)?;
+                if let Some(fourth) = fourth {
+                    write!(f, ",{}", fourth)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for MoveNotation {
+    type Err = NotationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (messenger, rest) = s
+            .trim()
+            .split_once('-')
+            .ok_or(NotationError::MissingDirection)?;
+        let (direction, throws) = rest.split_once('/').ok_or(NotationError::MissingThrow)?;
+
+        let throws = throws
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Direction>, _>>()?;
+        let extra_throws = match throws[..] {
+            [_] => None,
+            [_, second] => Some((second, None)),
+            [_, second, third] => Some((second, Some((third, None)))),
+            [_, second, third, fourth] => Some((second, Some((third, Some(fourth))))),
+            _ => return Err(NotationError::TooManyThrows(throws.len())),
+        };
+
+        Ok(MoveNotation {
+            messenger: messenger.parse()?,
+            direction: direction.parse()?,
+            first_throw: throws[0],
+            extra_throws,
+        })
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        MoveNotation::from(*self).fmt(f)
+    }
+}
+
+impl std::fmt::Display for LegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_move().fmt(f)
+    }
+}
+
+impl LegalMove {
+    /// Parse a move for the player to move in `game`.
+    pub fn from_notation(game: &Game, s: &str) -> Result<LegalMove, NotationError> {
+        let notation = s.parse::<MoveNotation>()?;
+        Ok(LegalMove::from_move(
+            game,
+            notation.for_player(game.to_move()),
+        )?)
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Direction {
+    type Err = NotationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Direction::*;
+        Ok(match s.trim() {
+            "NW2" => NW2,
+            "N2" => N2,
+            "NE2" => NE2,
+            "NW" => NW,
+            "N" => N,
+            "NE" => NE,
+            "W2" => W2,
+            "W" => W,
+            "E" => E,
+            "E2" => E2,
+            "SW" => SW,
+            "S" => S,
+            "SE" => SE,
+            "SW2" => SW2,
+            "S2" => S2,
+            "SE2" => SE2,
+            _ => return Err(NotationError::InvalidDirection(s.into())),
+        })
+    }
+}
+
+impl std::str::FromStr for BoardCoordinate {
+    type Err = NotationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NotationError::InvalidCoordinate(s.into());
+        let s = s.trim().as_bytes();
+        let [file, rank] = s else {
+            return Err(invalid());
+        };
+        let x = (file.to_ascii_lowercase() as i8).wrapping_sub(b'a' as i8) + 1;
+        let y = (*rank as i8).wrapping_sub(b'0' as i8);
+        BoardCoordinate::new(x, y).ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let notation = "d5-S/N,NE".parse::<MoveNotation>().unwrap();
+        assert_eq!(
+            notation,
+            MoveNotation {
+                messenger: BoardCoordinate::new(4, 5).unwrap(),
+                direction: Direction::S,
+                first_throw: Direction::N,
+                extra_throws: Some((Direction::NE, None)),
+            }
+        );
+        assert_eq!(notation.to_string(), "d5-S/N,NE");
+
+        assert_eq!(
+            "h5-S/N".parse::<MoveNotation>(),
+            Err(NotationError::InvalidCoordinate("h5".into()))
+        );
+        assert_eq!(
+            "d5-S/N,NE,N,N,N".parse::<MoveNotation>(),
+            Err(NotationError::TooManyThrows(5))
+        );
+        assert_eq!(
+            LegalMove::from_notation(&Game::default(), "d5-N/N"),
+            Err(NotationError::IllegalMove(MoveError::MoveToOccupied {
+                to: BoardCoordinate::new(4, 6).unwrap()
+            }))
+        );
+    }
+
+    #[test]
+    fn round_trip_legal_moves() {
+        for position in [
+            crate::STARTING_BOARD,
+            crate::BAD_THROW_2,
+            crate::BAD_THROW_3,
+        ] {
+            for player in [Player::Beige, Player::Black] {
+                let game = Game::from_position(position, player);
+                for move_ in game.legal_moves() {
+                    let parsed = LegalMove::from_notation(&game, &move_.to_string()).unwrap();
+                    assert_eq!(parsed, move_);
+                }
+            }
+        }
+    }
+}
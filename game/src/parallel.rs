@@ -0,0 +1,175 @@
+//! Parallel search using Lazy SMP: every thread searches the same position, sharing one
+//! transposition table. The threads mostly repeat each other's work, but each finds what the
+//! others have already searched in the table. To spread them out, each thread goes through the
+//! moves from the root in a different order, and half of them start a ply deeper, a quarter two
+//! plies deeper and so on.
+
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    eval::Evaluator,
+    search::{SearchLimits, SearchResult, Searcher, StopHandle},
+    tablebase::Tablebase,
+    Game, TranspositionTable,
+};
+
+pub struct ParallelSearcher<E> {
+    searchers: Vec<Searcher<E>>,
+    table: Arc<TranspositionTable>,
+    stop: StopHandle,
+}
+
+impl<E: Evaluator + Clone + Send> ParallelSearcher<E> {
+    /// Search with `threads` threads, or one if it's 0.
+    pub fn new(evaluate: E, threads: usize) -> ParallelSearcher<E> {
+        let table = Arc::new(TranspositionTable::default());
+        ParallelSearcher::with_table(evaluate, threads, table)
+    }
+
+    pub fn with_table(
+        evaluate: E,
+        threads: usize,
+        table: Arc<TranspositionTable>,
+    ) -> ParallelSearcher<E> {
+        ParallelSearcher {
+            searchers: (0..threads.max(1))
+                .map(|_| Searcher::with_table(evaluate.clone(), table.clone()))
+                .collect(),
+            table,
+            stop: StopHandle::default(),
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.searchers.len()
+    }
+
+    /// The transposition table shared by every thread, which is kept between searches.
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// Stops every thread. The search returns the best move from the deepest iteration any thread
+    /// finished.
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    pub fn set_tablebase(&mut self, tablebase: Arc<Tablebase>) {
+        for searcher in &mut self.searchers {
+            searcher.set_tablebase(tablebase.clone());
+        }
+    }
+
+    /// See [`Searcher::search`]. A node limit is split evenly between the threads.
+    pub fn search(&mut self, game: &Game, limits: SearchLimits) -> SearchResult {
+        self.search_with_progress(game, limits, |_| {})
+    }
+
+    /// [`ParallelSearcher::search`], calling `progress` whenever a thread completes an iteration
+    /// deeper than any before it. Its node count only includes the positions that thread
+    /// searched.
+    pub fn search_with_progress(
+        &mut self,
+        game: &Game,
+        limits: SearchLimits,
+        progress: impl FnMut(&SearchResult) + Send,
+    ) -> SearchResult {
+        let threads = self.searchers.len();
+        let limits = SearchLimits {
+            nodes: limits.nodes.map(|nodes| nodes.div_ceil(threads as u64)),
+            ..limits
+        };
+        let max_depth = limits.depth.unwrap_or(u32::MAX).max(1);
+
+        // stopped by the caller, or by the first thread to finish
+        let finished = self.stop.child();
+        let deepest = Mutex::new((0, progress));
+        let results = std::thread::scope(|scope| {
+            let searches = self
+                .searchers
+                .iter_mut()
+                .enumerate()
+                .map(|(i, searcher)| {
+                    searcher.stop = finished.clone();
+                    searcher.first_depth = (1 + (i + 1).trailing_zeros()).min(max_depth);
+                    searcher.root_rotation = i;
+                    let (finished, deepest) = (&finished, &deepest);
+                    scope.spawn(move || {
+                        let result = searcher.search_with_progress(game, limits, |result| {
+                            let mut deepest = deepest.lock().unwrap();
+                            if result.depth > deepest.0 {
+                                deepest.0 = result.depth;
+                                (deepest.1)(result);
+                            }
+                        });
+                        finished.stop();
+                        result
+                    })
+                })
+                .collect::<Vec<_>>();
+            searches
+                .into_iter()
+                .map(|search| search.join().expect("search thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let nodes = results.iter().map(|result| result.nodes).sum();
+        // the first thread wins ties
+        let mut result = results
+            .into_iter()
+            .rev()
+            .max_by_key(|result| (result.best_move.is_some(), result.depth))
+            .unwrap_or_default();
+        result.nodes = nodes;
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{eval::Heuristic, search::is_win_score, LegalMove};
+
+    #[test]
+    fn reaches_depth() {
+        let game = Game::from_position_string("3C3/2M1M2/7/7/7/2m1m2/3c3 beige").unwrap();
+        let limits = SearchLimits {
+            depth: Some(4),
+            nodes: None,
+            time: None,
+        };
+        let mut searcher = ParallelSearcher::new(Heuristic::default(), 4);
+        let mut deepest = 0;
+        let result = searcher.search_with_progress(&game, limits, |result| {
+            assert!(result.depth > deepest);
+            deepest = result.depth;
+        });
+
+        assert_eq!(result.depth, 4);
+        assert_eq!(deepest, 4);
+        assert!(!is_win_score(result.score));
+        let best_move = result.best_move.unwrap();
+        assert!(LegalMove::from_move(&game, best_move.to_move()).is_ok());
+        assert_eq!(result.pv.first(), Some(&best_move));
+        assert!(searcher.table().stats().hits > 0);
+    }
+
+    #[test]
+    fn stop_handle() {
+        let mut searcher = ParallelSearcher::new(Heuristic::default(), 2);
+        let stop = searcher.stop_handle();
+        stop.stop();
+        let result = searcher.search(&Game::default(), SearchLimits::default());
+        assert!(result.best_move.is_some());
+
+        // stopping one search doesn't stop the next once reset
+        stop.reset();
+        let limits = SearchLimits {
+            depth: Some(1),
+            nodes: None,
+            time: None,
+        };
+        assert_eq!(searcher.search(&Game::default(), limits).depth, 1);
+    }
+}
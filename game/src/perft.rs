@@ -0,0 +1,164 @@
+//! Counting the positions reachable in a number of moves, for checking move generation against
+//! known counts. Positions where the game is over have no moves, so they only count at depth 0.
+
+use crate::{Game, LegalMove};
+
+impl Game {
+    /// The number of move sequences of length `depth` from this position.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            .map(|move_| {
+                let mut child = *self;
+                child.make_move(move_).expect("legal move is legal");
+                child.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    /// [`Game::perft`] split up by the first move.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(LegalMove, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+        self.legal_moves()
+            .into_iter()
+            .map(|move_| {
+                let mut child = *self;
+                child.make_move(&move_).expect("legal move is legal");
+                (move_, child.perft(depth - 1))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BoardCoordinate, Move, Player, BAD_THROW_2, BAD_THROW_3, ONE_SQUARE, TWO_SQUARES};
+
+    fn positions() -> [Game; 3] {
+        [
+            Game::default(),
+            Game::from_position(BAD_THROW_2, Player::Black),
+            Game::from_position(BAD_THROW_3, Player::Black),
+        ]
+    }
+
+    #[test]
+    fn known_counts() {
+        let counts: [&[u64]; 3] = [
+            &[1, 98, 9932],
+            &[1, 37, 8716, 240673],
+            &[1, 52, 1793, 69874],
+        ];
+        for (game, counts) in positions().iter().zip(counts) {
+            for (depth, &count) in counts.iter().enumerate() {
+                assert_eq!(
+                    game.perft(depth as u32),
+                    count,
+                    "{}",
+                    game.to_position_string()
+                );
+            }
+            let divide = game.perft_divide(2);
+            assert_eq!(divide.len() as u64, counts[1]);
+            assert_eq!(
+                divide.iter().map(|(_, nodes)| nodes).sum::<u64>(),
+                counts[2]
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "slow, run with --release -- --ignored"]
+    fn deep_counts() {
+        let counts = [1188976, 57758102, 3209062];
+        let depths = [3, 4, 4];
+        for ((game, count), depth) in positions().iter().zip(counts).zip(depths) {
+            assert_eq!(game.perft(depth), count, "{}", game.to_position_string());
+        }
+    }
+
+    /// Every combination of directions, checked with [`Game::attempt_move`].
+    fn brute_force(game: &Game) -> Vec<Move> {
+        let mut throws = vec![vec![]];
+        let mut chains = vec![];
+        for _ in 0..4 {
+            throws = throws
+                .iter()
+                .flat_map(|throws: &Vec<_>| {
+                    ONE_SQUARE
+                        .iter()
+                        .map(move |&throw| [throws.as_slice(), &[throw]].concat())
+                })
+                .collect();
+            chains.extend(throws.iter().cloned());
+        }
+
+        let mut moves = vec![];
+        for messenger in (1..=7).flat_map(|x| (1..=7).flat_map(move |y| BoardCoordinate::new(x, y)))
+        {
+            for &direction in ONE_SQUARE.iter().chain(TWO_SQUARES.iter()) {
+                for throws in chains.iter() {
+                    let move_ = Move {
+                        player: game.to_move(),
+                        messenger,
+                        direction,
+                        first_throw: throws[0],
+                        extra_throws: match throws[1..] {
+                            [] => None,
+                            [b] => Some((b, None)),
+                            [b, c] => Some((b, Some((c, None)))),
+                            [b, c, d] => Some((b, Some((c, Some(d))))),
+                            _ => unreachable!(),
+                        },
+                    };
+                    if game.attempt_move(&move_).is_err() {
+                        continue;
+                    }
+
+                    // chains that put the piece back where the first throw did aren't
+                    // continued, since the position is the same as with one throw. Every throw
+                    // moves it, so this takes at least two more throws.
+                    let mut landing = ((messenger + direction).unwrap() + throws[0]).unwrap();
+                    let first_landing = landing;
+                    let returns = throws[1..].iter().any(|&throw| {
+                        landing = ((landing + throw).unwrap() + throw).unwrap();
+                        landing == first_landing
+                    });
+                    if !returns {
+                        moves.push(move_);
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    #[test]
+    fn complete_and_unique() {
+        for game in positions() {
+            let moves = game.legal_moves();
+            for (i, move_) in moves.iter().enumerate() {
+                assert!(
+                    !moves[i + 1..].contains(move_),
+                    "{move_} is generated twice"
+                );
+            }
+
+            let expected = brute_force(&game);
+            assert_eq!(moves.len(), expected.len(), "{}", game.to_position_string());
+            for move_ in expected {
+                assert!(moves.iter().any(|legal| legal.to_move() == move_));
+            }
+        }
+    }
+}
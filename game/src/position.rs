@@ -0,0 +1,219 @@
+//! Position strings, written as each row of the board from the 7th down to the 1st separated by
+//! `/`, followed by the player to move. Pieces use the same letters as the alternate `Debug`
+//! output of [`Board`]: `C`, `M` and `S` for beige cylinders, messengers and stunned messengers,
+//! and `c`, `m` and `s` for black ones. Runs of empty squares are written as a digit. The starting
+//! position is `3C3/2MMM2/3M3/7/3m3/2mmm2/3c3 beige`.
+
+use crate::{Board, Game, Piece, Player, Square};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    WrongNumberOfRows(usize),
+    WrongRowLength { row: usize, length: usize },
+    InvalidSquare { row: usize, c: char },
+    MissingPlayer,
+    InvalidPlayer(String),
+    TrailingInput(String),
+    TooManyCylinders(Player),
+    StunnedMessengerNotToMove(Player),
+}
+
+impl std::fmt::Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionError::WrongNumberOfRows(n) => write!(f, "expected 7 rows, found {}", n),
+            PositionError::WrongRowLength { row, length } => {
+                write!(f, "row {} has {} squares, expected 7", row, length)
+            }
+            PositionError::InvalidSquare { row, c } => {
+                write!(f, "row {} contains {:?}, which is not a piece", row, c)
+            }
+            PositionError::MissingPlayer => write!(f, "expected the player to move"),
+            PositionError::InvalidPlayer(s) => write!(f, "{:?} is not a player", s),
+            PositionError::TrailingInput(s) => write!(f, "unexpected {:?} after position", s),
+            PositionError::TooManyCylinders(player) => {
+                write!(f, "{:?} has more than one cylinder", player)
+            }
+            PositionError::StunnedMessengerNotToMove(player) => write!(
+                f,
+                "{:?} has a stunned messenger but just moved, so it should have been un-stunned",
+                player
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+impl Board {
+    pub fn to_position_string(&self) -> String {
+        let mut s = String::new();
+        for (i, row) in self.squares().iter().enumerate() {
+            if i != 0 {
+                s.push('/');
+            }
+            let mut empty = 0;
+            for square in row {
+                let c = match square {
+                    Square::Empty => {
+                        empty += 1;
+                        continue;
+                    }
+                    Square::Beige(Piece::Cylinder) => 'C',
+                    Square::Beige(Piece::Messenger) => 'M',
+                    Square::Beige(Piece::StunnedMessenger) => 'S',
+                    Square::Black(Piece::Cylinder) => 'c',
+                    Square::Black(Piece::Messenger) => 'm',
+                    Square::Black(Piece::StunnedMessenger) => 's',
+                };
+                if empty != 0 {
+                    s.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                s.push(c);
+            }
+            if empty != 0 {
+                s.push_str(&empty.to_string());
+            }
+        }
+        s
+    }
+
+    pub fn from_position_string(s: &str) -> Result<Board, PositionError> {
+        let rows = s.split('/').collect::<Vec<_>>();
+        if rows.len() != 7 {
+            return Err(PositionError::WrongNumberOfRows(rows.len()));
+        }
+
+        let mut board = [[Square::Empty; 7]; 7];
+        for (i, (row, text)) in board.iter_mut().zip(rows).enumerate() {
+            let row_number = 7 - i;
+            let mut squares = Vec::with_capacity(7);
+            for c in text.chars() {
+                let square = match c {
+                    '1'..='7' => {
+                        let n = c.to_digit(10).unwrap() as usize;
+                        squares.extend(std::iter::repeat_n(Square::Empty, n));
+                        continue;
+                    }
+                    'C' => Square::Beige(Piece::Cylinder),
+                    'M' => Square::Beige(Piece::Messenger),
+                    'S' => Square::Beige(Piece::StunnedMessenger),
+                    'c' => Square::Black(Piece::Cylinder),
+                    'm' => Square::Black(Piece::Messenger),
+                    's' => Square::Black(Piece::StunnedMessenger),
+                    _ => {
+                        return Err(PositionError::InvalidSquare { row: row_number, c });
+                    }
+                };
+                squares.push(square);
+            }
+            if squares.len() != 7 {
+                return Err(PositionError::WrongRowLength {
+                    row: row_number,
+                    length: squares.len(),
+                });
+            }
+            row.copy_from_slice(&squares);
+        }
+
+        let board = Board::from_squares(board);
+        for player in [Player::Beige, Player::Black] {
+            let cylinders = board
+                .squares()
+                .iter()
+                .flatten()
+                .filter(|square| square.is_cylinder() && square.is_players(player))
+                .count();
+            if cylinders > 1 {
+                return Err(PositionError::TooManyCylinders(player));
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+impl Game {
+    pub fn to_position_string(&self) -> String {
+        let player = match self.to_move {
+            Player::Beige => "beige",
+            Player::Black => "black",
+        };
+        format!("{} {}", self.board.to_position_string(), player)
+    }
+
+    pub fn from_position_string(s: &str) -> Result<Game, PositionError> {
+        let mut fields = s.split_whitespace();
+        let board = Board::from_position_string(fields.next().unwrap_or_default())?;
+        let to_move = match fields.next() {
+            Some("beige") => Player::Beige,
+            Some("black") => Player::Black,
+            Some(other) => return Err(PositionError::InvalidPlayer(other.into())),
+            None => return Err(PositionError::MissingPlayer),
+        };
+        if let Some(trailing) = fields.next() {
+            return Err(PositionError::TrailingInput(trailing.into()));
+        }
+
+        // stunned messengers are un-stunned once their owner moves, so only the player to move
+        // can have any
+        let just_moved = to_move.opponent();
+        if board
+            .squares()
+            .iter()
+            .flatten()
+            .any(|square| square.is_stunned_messenger() && square.is_players(just_moved))
+        {
+            return Err(PositionError::StunnedMessengerNotToMove(just_moved));
+        }
+
+        Ok(Game::from_position(board, to_move))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let game = Game::default();
+        let s = game.to_position_string();
+        assert_eq!(s, "3C3/2MMM2/3M3/7/3m3/2mmm2/3c3 beige");
+        let parsed = Game::from_position_string(&s).unwrap();
+        assert_eq!(parsed.to_position_string(), s);
+
+        let bad_throw_3 = Game::from_position(crate::BAD_THROW_3, Player::Black);
+        assert_eq!(
+            Game::from_position_string("2MMM2/1cm1m2/3mM2/7/7/7/7 black")
+                .unwrap()
+                .to_position_string(),
+            bad_throw_3.to_position_string()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Game::from_position_string("3C3/2MMM2/3M3/7/3m3/2mmm2 beige").unwrap_err(),
+            PositionError::WrongNumberOfRows(6)
+        );
+        assert_eq!(
+            Game::from_position_string("3C3/2MMM2/3M3/7/3m3/2mmm3/3c3 beige").unwrap_err(),
+            PositionError::WrongRowLength { row: 2, length: 8 }
+        );
+        assert_eq!(
+            Game::from_position_string("3C3/2MMM2/3X3/7/3m3/2mmm2/3c3 beige").unwrap_err(),
+            PositionError::InvalidSquare { row: 5, c: 'X' }
+        );
+        assert_eq!(
+            Game::from_position_string("3C3/2MMM2/3M3/7/3m3/2mmm2/3c3").unwrap_err(),
+            PositionError::MissingPlayer
+        );
+        assert_eq!(
+            Game::from_position_string("3C3/2MMM2/3S3/7/3m3/2mmm2/3c3 black").unwrap_err(),
+            PositionError::StunnedMessengerNotToMove(Player::Beige)
+        );
+    }
+}
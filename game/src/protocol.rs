@@ -0,0 +1,379 @@
+//! A line protocol for talking to engines, modelled on UCI. Each command and response is one line
+//! of whitespace-separated words. Commands, sent to the engine:
+//!
+//! - `cotw`, sent first. The engine replies `id name <name>` and then `cotwok`.
+//! - `isready`, answered with `readyok` once the engine can accept commands, even mid-search.
+//! - `newgame`, forgetting anything learned about previous games.
+//! - `position startpos [moves <move>...]` or `position <position> <player> [moves <move>...]`,
+//!   with the position as a [position string](crate::position) and the moves in
+//!   [`MoveNotation`].
+//! - `go [depth <plies>] [nodes <count>] [movetime <ms>] [infinite]`, searching the position until
+//!   a limit is reached or `stop` is sent. The engine sends `info` lines as it goes and then
+//!   `bestmove <move>`, or `bestmove none` if the game is over or the last `position` had an
+//!   illegal move.
+//! - `stop`, ending the search early.
+//! - `quit`.
+//!
+//! Responses, sent by the engine:
+//!
+//! - `info depth <plies> score (cp <score> | win <plies> | loss <plies>) nodes <count>
+//!   time <ms> pv <move>...`, once per completed iteration, with the score from the point of view
+//!   of the player to move.
+//! - `info string <text>`, for anything else the engine wants to say, like complaining about an
+//!   invalid command.
+//!
+//! ```text
+//! > cotw
+//! < id name cotw
+//! < cotwok
+//! > position startpos moves d5-S/N,NE
+//! > go depth 2
+//! < info depth 1 score cp 12 nodes 40 time 0 pv d2-N/S,W
+//! < info depth 2 score cp 3 nodes 2311 time 5 pv d2-N/S,W e6-S2/NE
+//! < bestmove d2-N/S,W
+//! ```
+
+use std::time::Duration;
+
+use crate::{
+    search::{is_win_score, SearchLimits, SearchResult, WIN},
+    Game, MoveNotation, NotationError, PositionError, Score,
+};
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Cotw,
+    IsReady,
+    NewGame,
+    Position {
+        /// `None` for the starting position.
+        start: Option<Game>,
+        moves: Vec<MoveNotation>,
+    },
+    Go(SearchLimits),
+    Stop,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub time: Duration,
+    pub pv: Vec<MoveNotation>,
+}
+
+impl Info {
+    pub fn from_result(result: &SearchResult, time: Duration) -> Info {
+        Info {
+            depth: result.depth,
+            score: result.score,
+            nodes: result.nodes,
+            time,
+            pv: result
+                .pv
+                .iter()
+                .map(|move_| move_.to_move().into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    Id {
+        name: String,
+    },
+    CotwOk,
+    ReadyOk,
+    Info(Info),
+    Message(String),
+    /// `None` if the game is over.
+    BestMove(Option<MoveNotation>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    Empty,
+    UnknownCommand(String),
+    MissingValue(&'static str),
+    InvalidNumber(String),
+    InvalidPosition(PositionError),
+    InvalidMove(NotationError),
+    TrailingInput(String),
+}
+
+impl std::fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolError::Empty => write!(f, "empty line"),
+            ProtocolError::UnknownCommand(s) => write!(f, "{:?} is not a command", s),
+            ProtocolError::MissingValue(name) => write!(f, "expected a value for {}", name),
+            ProtocolError::InvalidNumber(s) => write!(f, "{:?} is not a number", s),
+            ProtocolError::InvalidPosition(err) => write!(f, "invalid position: {}", err),
+            ProtocolError::InvalidMove(err) => write!(f, "invalid move: {}", err),
+            ProtocolError::TrailingInput(s) => write!(f, "unexpected {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<PositionError> for ProtocolError {
+    fn from(err: PositionError) -> Self {
+        ProtocolError::InvalidPosition(err)
+    }
+}
+
+impl From<NotationError> for ProtocolError {
+    fn from(err: NotationError) -> Self {
+        ProtocolError::InvalidMove(err)
+    }
+}
+
+fn number<'a, T: std::str::FromStr>(
+    words: &mut impl Iterator<Item = &'a str>,
+    name: &'static str,
+) -> Result<T, ProtocolError> {
+    let word = words.next().ok_or(ProtocolError::MissingValue(name))?;
+    word.parse()
+        .map_err(|_| ProtocolError::InvalidNumber(word.into()))
+}
+
+fn end<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<(), ProtocolError> {
+    match words.next() {
+        Some(word) => Err(ProtocolError::TrailingInput(word.into())),
+        None => Ok(()),
+    }
+}
+
+fn moves<'a>(words: impl Iterator<Item = &'a str>) -> Result<Vec<MoveNotation>, ProtocolError> {
+    Ok(words
+        .map(str::parse)
+        .collect::<Result<Vec<MoveNotation>, _>>()?)
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Cotw => write!(f, "cotw"),
+            Command::IsReady => write!(f, "isready"),
+            Command::NewGame => write!(f, "newgame"),
+            Command::Position { start, moves } => {
+                match start {
+                    Some(game) => write!(f, "position {}", game.to_position_string())?,
+                    None => write!(f, "position startpos")?,
+                }
+                if !moves.is_empty() {
+                    write!(f, " moves")?;
+                    for move_ in moves {
+                        write!(f, " {}", move_)?;
+                    }
+                }
+                Ok(())
+            }
+            Command::Go(limits) => {
+                write!(f, "go")?;
+                if let Some(depth) = limits.depth {
+                    write!(f, " depth {}", depth)?;
+                }
+                if let Some(nodes) = limits.nodes {
+                    write!(f, " nodes {}", nodes)?;
+                }
+                if let Some(time) = limits.time {
+                    write!(f, " movetime {}", time.as_millis())?;
+                }
+                if limits.depth.is_none() && limits.nodes.is_none() && limits.time.is_none() {
+                    write!(f, " infinite")?;
+                }
+                Ok(())
+            }
+            Command::Stop => write!(f, "stop"),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl std::str::FromStr for Command {
+    type Err = ProtocolError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let command = match words.next().ok_or(ProtocolError::Empty)? {
+            "cotw" => Command::Cotw,
+            "isready" => Command::IsReady,
+            "newgame" => Command::NewGame,
+            "position" => {
+                let start = match words
+                    .next()
+                    .ok_or(ProtocolError::MissingValue("position"))?
+                {
+                    "startpos" => None,
+                    board => {
+                        let player = words.next().ok_or(PositionError::MissingPlayer)?;
+                        Some(Game::from_position_string(&format!("{board} {player}"))?)
+                    }
+                };
+                let moves = match words.next() {
+                    Some("moves") => moves(&mut words)?,
+                    Some(word) => return Err(ProtocolError::TrailingInput(word.into())),
+                    None => Vec::new(),
+                };
+                Command::Position { start, moves }
+            }
+            "go" => {
+                let mut limits = SearchLimits::default();
+                while let Some(word) = words.next() {
+                    match word {
+                        "depth" => limits.depth = Some(number(&mut words, "depth")?),
+                        "nodes" => limits.nodes = Some(number(&mut words, "nodes")?),
+                        "movetime" => {
+                            limits.time =
+                                Some(Duration::from_millis(number(&mut words, "movetime")?))
+                        }
+                        "infinite" => {}
+                        word => return Err(ProtocolError::TrailingInput(word.into())),
+                    }
+                }
+                Command::Go(limits)
+            }
+            "stop" => Command::Stop,
+            "quit" => Command::Quit,
+            word => return Err(ProtocolError::UnknownCommand(word.into())),
+        };
+        end(words)?;
+        Ok(command)
+    }
+}
+
+impl std::fmt::Display for Response {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Response::Id { name } => write!(f, "id name {}", name),
+            Response::CotwOk => write!(f, "cotwok"),
+            Response::ReadyOk => write!(f, "readyok"),
+            Response::Info(info) => {
+                write!(f, "info depth {} score ", info.depth)?;
+                match info.score {
+                    score if is_win_score(score) && score > 0 => write!(f, "win {}", WIN - score)?,
+                    score if is_win_score(score) => write!(f, "loss {}", WIN + score)?,
+                    score => write!(f, "cp {}", score)?,
+                }
+                write!(f, " nodes {} time {} pv", info.nodes, info.time.as_millis())?;
+                for move_ in &info.pv {
+                    write!(f, " {}", move_)?;
+                }
+                Ok(())
+            }
+            Response::Message(message) => write!(f, "info string {}", message),
+            Response::BestMove(Some(move_)) => write!(f, "bestmove {}", move_),
+            Response::BestMove(None) => write!(f, "bestmove none"),
+        }
+    }
+}
+
+impl std::str::FromStr for Response {
+    type Err = ProtocolError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let response = match words.next().ok_or(ProtocolError::Empty)? {
+            "id" => match words.next() {
+                Some("name") => {
+                    return Ok(Response::Id {
+                        name: words.collect::<Vec<_>>().join(" "),
+                    })
+                }
+                _ => return Err(ProtocolError::MissingValue("id name")),
+            },
+            "cotwok" => Response::CotwOk,
+            "readyok" => Response::ReadyOk,
+            "info" => {
+                let mut info = Info {
+                    depth: 0,
+                    score: 0,
+                    nodes: 0,
+                    time: Duration::ZERO,
+                    pv: Vec::new(),
+                };
+                while let Some(word) = words.next() {
+                    match word {
+                        "string" => {
+                            return Ok(Response::Message(words.collect::<Vec<_>>().join(" ")))
+                        }
+                        "depth" => info.depth = number(&mut words, "depth")?,
+                        "score" => {
+                            info.score = match words.next() {
+                                Some("cp") => number(&mut words, "score")?,
+                                Some("win") => WIN - number::<Score>(&mut words, "score")?,
+                                Some("loss") => -WIN + number::<Score>(&mut words, "score")?,
+                                _ => return Err(ProtocolError::MissingValue("score")),
+                            }
+                        }
+                        "nodes" => info.nodes = number(&mut words, "nodes")?,
+                        "time" => info.time = Duration::from_millis(number(&mut words, "time")?),
+                        "pv" => {
+                            info.pv = moves(&mut words)?;
+                        }
+                        word => return Err(ProtocolError::TrailingInput(word.into())),
+                    }
+                }
+                Response::Info(info)
+            }
+            "bestmove" => match words.next() {
+                Some("none") => Response::BestMove(None),
+                Some(move_) => Response::BestMove(Some(move_.parse()?)),
+                None => return Err(ProtocolError::MissingValue("bestmove")),
+            },
+            word => return Err(ProtocolError::UnknownCommand(word.into())),
+        };
+        end(words)?;
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BAD_THROW_2;
+
+    #[test]
+    fn round_trip() {
+        let commands = [
+            "cotw",
+            "isready",
+            "position startpos",
+            "position startpos moves d6-S/S d2-N/N",
+            &format!(
+                "position {} moves d6-S/S",
+                Game::from_position(BAD_THROW_2, crate::Player::Black).to_position_string()
+            ),
+            "go depth 4 nodes 1000 movetime 250",
+            "go infinite",
+            "stop",
+            "quit",
+        ];
+        for command in commands {
+            assert_eq!(command.parse::<Command>().unwrap().to_string(), command);
+        }
+
+        let responses = [
+            "id name cotw 0.1",
+            "cotwok",
+            "info depth 3 score cp -12 nodes 4000 time 15 pv d2-N/S,W e6-S2/NE",
+            "info depth 5 score win 3 nodes 1 time 0 pv d2-N/S,W",
+            "info depth 5 score loss 4 nodes 1 time 0 pv",
+            "info string hello there",
+            "bestmove d5-S/N,NE",
+            "bestmove none",
+        ];
+        for response in responses {
+            assert_eq!(response.parse::<Response>().unwrap().to_string(), response);
+        }
+
+        assert_eq!(
+            "go depth".parse::<Command>().unwrap_err(),
+            ProtocolError::MissingValue("depth")
+        );
+        assert!("position startpos moves d9-S/S".parse::<Command>().is_err());
+    }
+}
@@ -0,0 +1,618 @@
+//! Game records, modelled on PGN. A record is a list of `[Name "value"]` tags followed by the
+//! moves in [`MoveNotation`], with optional move numbers, `{comments}`, `(variations)` and a
+//! result of `1-0` (beige won), `0-1` (black won), `1/2-1/2` or `*` (unfinished).
+//!
+//! ```text
+//! [Beige "someone"]
+//! [Black "someone else"]
+//! [Result "*"]
+//!
+//! 1. d5-S/N,NE {stuns d3} 1... d2-N/S,W (1... d2-N/S) 2. e6-S2/NE *
+//! ```
+//!
+//! Tag values escape `"` and `\` with a backslash. A `Result` tag has to agree with the result
+//! after the moves. A `Position` tag holds the [position string](crate::position) the game
+//! started from.
+
+use crate::{
+    Game, GameHistory, LegalMove, MoveError, MoveNotation, NotationError, Outcome, Player,
+    PositionError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordResult {
+    BeigeWon,
+    BlackWon,
+    Draw,
+    Unfinished,
+}
+
+impl RecordResult {
+    pub fn from_outcome(outcome: Option<Outcome>) -> RecordResult {
+        match outcome.map(|outcome| outcome.winner()) {
+            Some(Some(Player::Beige)) => RecordResult::BeigeWon,
+            Some(Some(Player::Black)) => RecordResult::BlackWon,
+            Some(None) => RecordResult::Draw,
+            None => RecordResult::Unfinished,
+        }
+    }
+
+    fn parse(s: &str) -> Option<RecordResult> {
+        match s {
+            "1-0" => Some(RecordResult::BeigeWon),
+            "0-1" => Some(RecordResult::BlackWon),
+            "1/2-1/2" => Some(RecordResult::Draw),
+            "*" => Some(RecordResult::Unfinished),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RecordResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordResult::BeigeWon => write!(f, "1-0"),
+            RecordResult::BlackWon => write!(f, "0-1"),
+            RecordResult::Draw => write!(f, "1/2-1/2"),
+            RecordResult::Unfinished => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMove {
+    pub notation: MoveNotation,
+    pub comment: Option<String>,
+    /// Lines played instead of this move.
+    pub variations: Vec<Vec<RecordedMove>>,
+}
+
+impl RecordedMove {
+    pub fn new(notation: MoveNotation) -> RecordedMove {
+        RecordedMove {
+            notation,
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub tags: Vec<(String, String)>,
+    pub comment: Option<String>,
+    pub moves: Vec<RecordedMove>,
+    pub result: RecordResult,
+}
+
+impl Default for GameRecord {
+    fn default() -> Self {
+        GameRecord {
+            tags: Vec::new(),
+            comment: None,
+            moves: Vec::new(),
+            result: RecordResult::Unfinished,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    InvalidTag {
+        line: usize,
+    },
+    UnterminatedComment {
+        line: usize,
+    },
+    UnbalancedVariation {
+        line: usize,
+    },
+    InvalidMove {
+        line: usize,
+        text: String,
+        error: NotationError,
+    },
+    MissingResult,
+    /// The `Result` tag says something different to the result after the moves.
+    ResultMismatch {
+        tag: String,
+        result: RecordResult,
+    },
+}
+
+impl std::fmt::Display for RecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordError::InvalidTag { line } => write!(f, "line {}: invalid tag", line),
+            RecordError::UnterminatedComment { line } => {
+                write!(f, "line {}: comment is never closed", line)
+            }
+            RecordError::UnbalancedVariation { line } => {
+                write!(f, "line {}: unbalanced parentheses", line)
+            }
+            RecordError::InvalidMove { line, text, error } => {
+                write!(f, "line {}: {:?} is not a move: {}", line, text, error)
+            }
+            RecordError::MissingResult => write!(f, "the record does not end with a result"),
+            RecordError::ResultMismatch { tag, result } => write!(
+                f,
+                "the Result tag is {:?} but the record ends with {}",
+                tag, result
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    Position(PositionError),
+    IllegalMove {
+        /// Starting from 1 for the first move in the record.
+        ply: usize,
+        notation: MoveNotation,
+        error: MoveError,
+    },
+    /// The moves end the game with a different result to the one recorded.
+    WrongResult {
+        recorded: RecordResult,
+        played: RecordResult,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Position(err) => write!(f, "invalid starting position: {}", err),
+            ReplayError::IllegalMove {
+                ply,
+                notation,
+                error,
+            } => write!(f, "ply {} ({}) is illegal: {}", ply, notation, error),
+            ReplayError::WrongResult { recorded, played } => write!(
+                f,
+                "the record ends with {} but the moves end the game with {}",
+                recorded, played
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl GameRecord {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.into(),
+            None => self.tags.push((name.into(), value.into())),
+        }
+    }
+
+    pub fn from_history(history: &GameHistory) -> GameRecord {
+        let mut record = GameRecord {
+            moves: history
+                .moves()
+                .map(|move_| RecordedMove::new(move_.to_move().into()))
+                .collect(),
+            result: RecordResult::from_outcome(history.game().outcome()),
+            ..Default::default()
+        };
+        record.set_tag("Result", &record.result.to_string());
+        if history.start().to_position_string() != Game::default().to_position_string() {
+            record.set_tag("Position", &history.start().to_position_string());
+        }
+        record
+    }
+
+    pub fn starting_position(&self) -> Result<Game, PositionError> {
+        match self.tag("Position") {
+            Some(position) => Game::from_position_string(position),
+            None => Ok(Game::default()),
+        }
+    }
+
+    /// Play the main line of the record from its starting position. If the moves end the game,
+    /// the recorded result has to be the one they end it with, or `*`. Games the moves don't end
+    /// can have any result, since they may have been resigned, forfeited or adjudicated.
+    pub fn replay(&self) -> Result<GameHistory, ReplayError> {
+        let mut history =
+            GameHistory::new(self.starting_position().map_err(ReplayError::Position)?);
+        for (i, recorded) in self.moves.iter().enumerate() {
+            let illegal = |error| ReplayError::IllegalMove {
+                ply: i + 1,
+                notation: recorded.notation,
+                error,
+            };
+            let move_ = recorded.notation.for_player(history.game().to_move());
+            let legal = LegalMove::from_move(history.game(), move_).map_err(illegal)?;
+            history.make_move(&legal).map_err(illegal)?;
+        }
+
+        let played = RecordResult::from_outcome(history.game().outcome());
+        if played != RecordResult::Unfinished
+            && self.result != RecordResult::Unfinished
+            && self.result != played
+        {
+            return Err(ReplayError::WrongResult {
+                recorded: self.result,
+                played,
+            });
+        }
+        Ok(history)
+    }
+
+    /// Read every record in `s`.
+    pub fn parse_all(s: &str) -> Result<Vec<GameRecord>, RecordError> {
+        let mut parser = Parser { s, line: 1 };
+        let mut records = Vec::new();
+        while {
+            parser.skip_whitespace();
+            !parser.s.is_empty()
+        } {
+            records.push(parser.record()?);
+        }
+        Ok(records)
+    }
+}
+
+impl std::str::FromStr for GameRecord {
+    type Err = RecordError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { s, line: 1 };
+        let record = parser.record()?;
+        parser.skip_whitespace();
+        match parser.s.is_empty() {
+            true => Ok(record),
+            false => Err(RecordError::InvalidTag { line: parser.line }),
+        }
+    }
+}
+
+struct Parser<'s> {
+    s: &'s str,
+    line: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn advance(&mut self, n: usize) -> &'s str {
+        let (taken, rest) = self.s.split_at(n);
+        self.line += taken.matches('\n').count();
+        self.s = rest;
+        taken
+    }
+
+    fn skip_whitespace(&mut self) {
+        let n = self.s.len() - self.s.trim_start().len();
+        self.advance(n);
+    }
+
+    fn record(&mut self) -> Result<GameRecord, RecordError> {
+        let mut record = GameRecord::default();
+
+        loop {
+            self.skip_whitespace();
+            if !self.s.starts_with('[') {
+                break;
+            }
+            let tag = self.tag()?;
+            record.tags.push(tag);
+        }
+
+        self.skip_whitespace();
+        if self.s.starts_with('{') {
+            record.comment = Some(self.comment()?);
+        }
+
+        let (moves, result) = self.moves(0)?;
+        record.moves = moves;
+        record.result = result.ok_or(RecordError::MissingResult)?;
+        if let Some(tag) = record.tag("Result") {
+            if RecordResult::parse(tag) != Some(record.result) {
+                return Err(RecordError::ResultMismatch {
+                    tag: tag.into(),
+                    result: record.result,
+                });
+            }
+        }
+        Ok(record)
+    }
+
+    /// A `[Name "value"]` tag. Inside the value, `\` escapes the next character.
+    fn tag(&mut self) -> Result<(String, String), RecordError> {
+        let line = self.line;
+        let invalid = || RecordError::InvalidTag { line };
+        self.advance(1);
+        self.skip_whitespace();
+        let end = self
+            .s
+            .find(|c: char| c.is_whitespace() || c == '"' || c == ']')
+            .unwrap_or(self.s.len());
+        let name = self.advance(end).to_string();
+        self.skip_whitespace();
+        if name.is_empty() || !self.s.starts_with('"') {
+            return Err(invalid());
+        }
+        self.advance(1);
+
+        let mut value = String::new();
+        let mut chars = self.s.char_indices();
+        let end = loop {
+            match chars.next().ok_or_else(invalid)? {
+                (i, '"') => break i,
+                (_, '\\') => value.push(chars.next().ok_or_else(invalid)?.1),
+                (_, c) => value.push(c),
+            }
+        };
+        self.advance(end + 1);
+        self.skip_whitespace();
+        match self.s.starts_with(']') {
+            true => {
+                self.advance(1);
+                Ok((name, value))
+            }
+            false => Err(invalid()),
+        }
+    }
+
+    fn comment(&mut self) -> Result<String, RecordError> {
+        let line = self.line;
+        let end = self
+            .s
+            .find('}')
+            .ok_or(RecordError::UnterminatedComment { line })?;
+        Ok(self.advance(end + 1)[1..end].trim().into())
+    }
+
+    fn moves(
+        &mut self,
+        depth: usize,
+    ) -> Result<(Vec<RecordedMove>, Option<RecordResult>), RecordError> {
+        let mut moves: Vec<RecordedMove> = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            let line = self.line;
+
+            if self.s.is_empty() || self.s.starts_with('[') {
+                return match depth {
+                    0 => Ok((moves, None)),
+                    _ => Err(RecordError::UnbalancedVariation { line }),
+                };
+            } else if self.s.starts_with('{') {
+                let comment = self.comment()?;
+                match moves.last_mut() {
+                    Some(last) => last.comment = Some(comment),
+                    None => return Err(RecordError::UnbalancedVariation { line }),
+                }
+            } else if self.s.starts_with('(') {
+                self.advance(1);
+                let (variation, _) = self.moves(depth + 1)?;
+                match moves.last_mut() {
+                    Some(last) => last.variations.push(variation),
+                    None => return Err(RecordError::UnbalancedVariation { line }),
+                }
+            } else if self.s.starts_with(')') {
+                self.advance(1);
+                return match depth {
+                    0 => Err(RecordError::UnbalancedVariation { line }),
+                    _ => Ok((moves, None)),
+                };
+            } else {
+                let end = self
+                    .s
+                    .find(|c: char| c.is_whitespace() || "{}()[".contains(c))
+                    .unwrap_or(self.s.len());
+                let token = self.advance(end);
+
+                if let Some(result) = RecordResult::parse(token) {
+                    return match depth {
+                        0 => Ok((moves, Some(result))),
+                        _ => Err(RecordError::UnbalancedVariation { line }),
+                    };
+                }
+
+                // move numbers like `12.` or `12...`
+                let number = token.trim_end_matches('.');
+                if number.len() != token.len() && number.bytes().all(|b| b.is_ascii_digit()) {
+                    continue;
+                }
+
+                let notation = token.parse().map_err(|error| RecordError::InvalidMove {
+                    line,
+                    text: token.into(),
+                    error,
+                })?;
+                moves.push(RecordedMove::new(notation));
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in self.tags.iter() {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{} \"{}\"]", name, value)?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut tokens = Vec::new();
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        let black_first = self
+            .starting_position()
+            .map(|game| game.to_move() == Player::Black)
+            .unwrap_or(false);
+        write_moves(&mut tokens, &self.moves, black_first as usize, true);
+        tokens.push(self.result.to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length != 0 && line_length + 1 + token.len() > 80 {
+                writeln!(f)?;
+                line_length = 0;
+            }
+            if line_length != 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// `ply` counts from 0 for beige's first move, so black's moves have odd plies.
+fn write_moves(tokens: &mut Vec<String>, moves: &[RecordedMove], ply: usize, mut number: bool) {
+    for (i, move_) in moves.iter().enumerate() {
+        let ply = ply + i;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(move_.notation.to_string());
+
+        number = false;
+        if let Some(comment) = &move_.comment {
+            tokens.push(format!("{{{}}}", comment));
+            number = true;
+        }
+        for variation in move_.variations.iter() {
+            let start = tokens.len();
+            write_moves(tokens, variation, ply, true);
+            if tokens.len() != start {
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+            }
+            number = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RECORD: &str = r#"[Beige "someone"]
+[Black "someone else"]
+[Result "*"]
+
+{short} 1. d5-S/N,NE {stun} 1... d2-N/S,W (1... d2-N/S 2. c6-S/N) 2. e6-S2/NE *
+"#;
+
+    #[test]
+    fn read_write() {
+        let record = RECORD.parse::<GameRecord>().unwrap();
+        assert_eq!(record.to_string(), RECORD);
+        assert_eq!(record.tag("Black"), Some("someone else"));
+        assert_eq!(record.comment.as_deref(), Some("short"));
+        assert_eq!(record.moves.len(), 3);
+        assert_eq!(record.moves[0].comment.as_deref(), Some("stun"));
+        assert_eq!(record.moves[1].variations.len(), 1);
+        assert_eq!(record.moves[1].variations[0].len(), 2);
+        assert_eq!(record.result, RecordResult::Unfinished);
+
+        let written = record.to_string();
+        assert_eq!(written.parse::<GameRecord>().unwrap(), record);
+
+        let history = record.replay().unwrap();
+        assert_eq!(history.ply(), 3);
+        let rewritten = GameRecord::from_history(&history);
+        assert_eq!(rewritten.replay().unwrap().ply(), 3);
+    }
+
+    #[test]
+    fn illegal_move() {
+        let record = "1. d5-S/N,NE d2-N/S,W 2. d2-N/S *"
+            .parse::<GameRecord>()
+            .unwrap();
+        assert!(matches!(
+            record.replay(),
+            Err(ReplayError::IllegalMove {
+                ply: 3,
+                error: MoveError::NotPlayersPiece { .. },
+                ..
+            })
+        ));
+
+        assert_eq!(
+            GameRecord::parse_all("1. d5-S/N 1-0\n\n[Result \"0-1\"]\n1. d5-S/N 0-1")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            "1. d5-S/N (d5-S/N".parse::<GameRecord>(),
+            Err(RecordError::UnbalancedVariation { line: 1 })
+        );
+    }
+
+    #[test]
+    fn finished_game() {
+        let record = |moves: &str| {
+            format!("[Position \"7/2m4/3c3/7/6M/3C3/7 black\"]\n{}", moves)
+                .parse::<GameRecord>()
+                .unwrap()
+                .replay()
+        };
+        assert!(record("1... c6-E/N 0-1").unwrap().game().is_over());
+        assert!(record("1... c6-E/N *").is_ok());
+        assert_eq!(
+            record("1... c6-E/N 1-0").unwrap_err(),
+            ReplayError::WrongResult {
+                recorded: RecordResult::BeigeWon,
+                played: RecordResult::BlackWon,
+            }
+        );
+        assert!(matches!(
+            record("1... c6-E/N 2. g3-W/N 0-1"),
+            Err(ReplayError::IllegalMove {
+                ply: 2,
+                error: MoveError::GameOver(_),
+                ..
+            })
+        ));
+        // forfeited or adjudicated
+        assert!(record("1/2-1/2").is_ok());
+    }
+
+    #[test]
+    fn tags() {
+        let mut record = GameRecord::default();
+        record.set_tag("Event", r#"a "quoted" [name] \ more"#);
+        record.set_tag("Empty", "");
+        let written = record.to_string();
+        assert_eq!(written.parse::<GameRecord>().unwrap(), record);
+
+        let record = "[ Beige  \"]\" ]\n*".parse::<GameRecord>().unwrap();
+        assert_eq!(record.tag("Beige"), Some("]"));
+        assert_eq!(
+            "[Beige \"a\" b]\n*".parse::<GameRecord>(),
+            Err(RecordError::InvalidTag { line: 1 })
+        );
+        assert_eq!(
+            "\n[Beige \"a\\\"]\n*".parse::<GameRecord>(),
+            Err(RecordError::InvalidTag { line: 2 })
+        );
+        assert_eq!(
+            "[Result \"1-0\"]\n1. d5-S/N 0-1".parse::<GameRecord>(),
+            Err(RecordError::ResultMismatch {
+                tag: "1-0".into(),
+                result: RecordResult::BlackWon,
+            })
+        );
+    }
+}
@@ -0,0 +1,33 @@
+/// A small, fast pseudorandom number generator (SplitMix64). The same seed always produces the
+/// same numbers, so games played with it can be reproduced.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let (state, value) = splitmix(self.state);
+        self.state = state;
+        value
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// One step of SplitMix64, returning the next state and the number it produces. It's a `const fn`
+/// so that constant tables like the [zobrist keys](crate::zobrist) can be generated with it.
+pub(crate) const fn splitmix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    (state, z ^ (z >> 31))
+}
@@ -0,0 +1,462 @@
+//! Iterative deepening alpha-beta search, in negamax form so that every score is from the point
+//! of view of the player to move.
+
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    eval::{Evaluator, Heuristic},
+    tablebase::{TableResult, Tablebase},
+    tt::{Bound, Entry, TranspositionTable},
+    Game, LegalMove, MoveOrder, Outcome, Player,
+};
+
+pub type Score = i32;
+
+/// The score of a won game. Wins found sooner score higher, so a win in `n` plies scores
+/// `WIN - n`.
+pub const WIN: Score = 1_000_000;
+const INFINITY: Score = WIN + 1;
+
+pub fn is_win_score(score: Score) -> bool {
+    score.abs() > WIN - 1000
+}
+
+/// Win scores depend on how far the win is from the root, but the table is shared between
+/// searches from different roots, so they're stored relative to the position instead.
+fn to_table(score: Score, ply: u32) -> Score {
+    match score {
+        score if is_win_score(score) => score + score.signum() * ply as Score,
+        score => score,
+    }
+}
+
+fn from_table(score: Score, ply: u32) -> Score {
+    match score {
+        score if is_win_score(score) => score - score.signum() * ply as Score,
+        score => score,
+    }
+}
+
+pub(crate) fn terminal_score(outcome: Outcome, to_move: Player, ply: u32) -> Score {
+    match outcome.winner() {
+        Some(winner) if winner == to_move => WIN - ply as Score,
+        Some(_) => -(WIN - ply as Score),
+        None => 0,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchLimits {
+    /// The deepest iteration to search to.
+    pub depth: Option<u32>,
+    /// Stop searching after visiting this many positions, once the first iteration is finished.
+    pub nodes: Option<u64>,
+    /// Stop searching after this long, once the first iteration is finished.
+    pub time: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchResult {
+    /// `None` if the game is already over, or if the search was stopped by a [`StopHandle`]
+    /// before it found any move.
+    pub best_move: Option<LegalMove>,
+    pub score: Score,
+    /// The depth of the deepest completed iteration.
+    pub depth: u32,
+    pub nodes: u64,
+    /// The moves both players are expected to make, starting with the best move.
+    pub pv: Vec<LegalMove>,
+}
+
+/// Stops a search from another thread. The searcher keeps the best move from the iterations it
+/// finished.
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle {
+    stopped: Arc<AtomicBool>,
+    /// A handle that stops this one too, without being stopped by it.
+    parent: Option<Arc<AtomicBool>>,
+}
+
+impl StopHandle {
+    /// Stop the current search, and any started before [`StopHandle::reset`] is called.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.stopped.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+            || self
+                .parent
+                .as_ref()
+                .is_some_and(|parent| parent.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn child(&self) -> StopHandle {
+        StopHandle {
+            stopped: Arc::default(),
+            parent: Some(self.stopped.clone()),
+        }
+    }
+}
+
+pub struct Searcher<E> {
+    evaluate: E,
+    limits: SearchLimits,
+    deadline: Option<Instant>,
+    pub(crate) stop: StopHandle,
+    /// The depth of the first iteration, so that parallel searchers can start at different depths.
+    pub(crate) first_depth: u32,
+    /// How far to rotate the order of the moves from the root, so that parallel searchers go
+    /// through them in different orders.
+    pub(crate) root_rotation: usize,
+    nodes: u64,
+    /// Whether the node and time limits apply, which they don't during the first iteration.
+    limited: bool,
+    stopped: bool,
+    table: Arc<TranspositionTable>,
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl<E: Evaluator> Searcher<E> {
+    pub fn new(evaluate: E) -> Searcher<E> {
+        Searcher::with_table(evaluate, Arc::new(TranspositionTable::default()))
+    }
+
+    /// Use `table`, which can be shared with other searchers, including ones on other threads.
+    pub fn with_table(evaluate: E, table: Arc<TranspositionTable>) -> Searcher<E> {
+        Searcher {
+            evaluate,
+            limits: SearchLimits::default(),
+            deadline: None,
+            stop: StopHandle::default(),
+            first_depth: 1,
+            root_rotation: 0,
+            nodes: 0,
+            limited: false,
+            stopped: false,
+            table,
+            tablebase: None,
+        }
+    }
+
+    /// The transposition table, which is kept between searches.
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    /// Score positions in `tablebase` by their result instead of searching them.
+    pub fn set_tablebase(&mut self, tablebase: Arc<Tablebase>) {
+        self.tablebase = Some(tablebase);
+    }
+
+    /// Search deeper and deeper until a limit is reached. Without a depth or node limit this
+    /// only stops when the game has been solved.
+    pub fn search(&mut self, game: &Game, limits: SearchLimits) -> SearchResult {
+        self.search_with_progress(game, limits, |_| {})
+    }
+
+    /// [`Searcher::search`], calling `progress` with the result of each completed iteration.
+    pub fn search_with_progress(
+        &mut self,
+        game: &Game,
+        limits: SearchLimits,
+        mut progress: impl FnMut(&SearchResult),
+    ) -> SearchResult {
+        self.limits = limits;
+        // only asked for when needed, since there's no clock on wasm
+        self.deadline = limits.time.map(|time| Instant::now() + time);
+        self.nodes = 0;
+        self.stopped = false;
+
+        let mut result = SearchResult::default();
+        if game.is_over() {
+            return result;
+        }
+
+        let max_depth = limits.depth.unwrap_or(u32::MAX);
+        let mut depth = self.first_depth;
+        // always finish the first iteration unless stopped, so there's a move to make
+        while depth <= max_depth && (depth == self.first_depth || !self.out_of_time()) {
+            self.limited = depth != self.first_depth;
+            let mut pv = Vec::new();
+            let score = self.negamax(game, depth, 0, -INFINITY, INFINITY, &mut pv);
+
+            if self.stopped {
+                // keep the best move from an unfinished iteration only if we have nothing else
+                if result.best_move.is_none() && !pv.is_empty() {
+                    result.best_move = pv.first().copied();
+                    result.pv = pv;
+                }
+                break;
+            }
+
+            result.best_move = pv.first().copied();
+            result.score = score;
+            result.depth = depth;
+            result.pv = pv;
+            result.nodes = self.nodes;
+            progress(&result);
+
+            if is_win_score(score) {
+                break;
+            }
+            depth += 1;
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    fn out_of_time(&self) -> bool {
+        self.stop.is_stopped()
+            || (self.limited
+                && self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline))
+    }
+
+    fn negamax(
+        &mut self,
+        game: &Game,
+        depth: u32,
+        ply: u32,
+        mut alpha: Score,
+        beta: Score,
+        pv: &mut Vec<LegalMove>,
+    ) -> Score {
+        pv.clear();
+        self.nodes += 1;
+        if (self.limited && self.limits.nodes.is_some_and(|limit| self.nodes > limit))
+            || (self.nodes.is_multiple_of(256) && self.out_of_time())
+        {
+            self.stopped = true;
+            return 0;
+        }
+
+        if let Some(outcome) = game.outcome() {
+            return terminal_score(outcome, game.to_move(), ply);
+        }
+        // the root is searched anyway so there's a move to return
+        if let Some(result) = self
+            .tablebase
+            .as_ref()
+            .filter(|_| ply != 0)
+            .and_then(|tablebase| tablebase.probe(game))
+        {
+            return match result {
+                TableResult::Win(distance) => WIN - (ply + distance as u32) as Score,
+                TableResult::Loss(distance) => -(WIN - (ply + distance as u32) as Score),
+                TableResult::Draw => 0,
+            };
+        }
+        if depth == 0 {
+            return self.evaluate.evaluate(game);
+        }
+
+        let key = game.zobrist();
+        let mut table_move = None;
+        if let Some(entry) = self.table.probe(key) {
+            table_move = entry.best_move;
+            let score = from_table(entry.score, ply);
+            // always search the root so there's a best move to return
+            if ply != 0 && entry.depth >= depth {
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if cutoff {
+                    pv.extend(entry.best_move);
+                    return score;
+                }
+            }
+        }
+
+        // the best move from earlier searches of this position is searched first, then moves
+        // that stun something
+        let table_move = table_move.and_then(|move_| Some((move_, game.board_after(&move_).ok()?)));
+        let rotated = ply == 0 && self.root_rotation != 0;
+        let root_moves = rotated.then(|| {
+            let mut moves = game.moves(MoveOrder::StunsFirst).collect::<Vec<_>>();
+            let rotation = self.root_rotation % moves.len().max(1);
+            moves.rotate_left(rotation);
+            moves
+        });
+        let moves = table_move
+            .into_iter()
+            .chain(root_moves.into_iter().flatten())
+            .chain(
+                (!rotated)
+                    .then(|| game.moves(MoveOrder::StunsFirst))
+                    .into_iter()
+                    .flatten(),
+            );
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = Vec::new();
+        // moves that leave the same position only need searching once
+        let mut searched = HashSet::new();
+        for (move_, board) in moves {
+            if !searched.insert(board) {
+                continue;
+            }
+            let child = game.after(board);
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                best_move = Some(move_);
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(move_);
+                    pv.append(&mut child_pv);
+                }
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(Entry {
+            key,
+            depth,
+            score: to_table(best, ply),
+            bound,
+            best_move,
+        });
+
+        best
+    }
+}
+
+/// Search `game` with the default [`Heuristic`].
+pub fn search(game: &Game, limits: SearchLimits) -> SearchResult {
+    Searcher::new(Heuristic::default()).search(game, limits)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_win() {
+        let game = Game::from_position_string("7/2m4/3c3/7/6M/3C3/7 black").unwrap();
+        let result = search(
+            &game,
+            SearchLimits {
+                depth: Some(3),
+                nodes: None,
+                time: None,
+            },
+        );
+        assert_eq!(result.best_move.unwrap().to_string(), "c6-E/N");
+        assert_eq!(result.score, WIN - 1);
+        assert_eq!(result.depth, 1);
+        assert_eq!(result.pv.len(), 1);
+    }
+
+    #[test]
+    fn node_limit() {
+        let game = Game::default();
+        let result = search(
+            &game,
+            SearchLimits {
+                depth: None,
+                nodes: Some(2000),
+                time: None,
+            },
+        );
+        assert!(result.nodes <= 2001);
+        assert!(result.depth >= 1);
+        let best_move = result.best_move.unwrap();
+        assert_eq!(result.pv[0], best_move);
+        assert!(LegalMove::from_move(&game, best_move.to_move()).is_ok());
+    }
+
+    #[test]
+    fn first_iteration_is_finished() {
+        let game = Game::default();
+        let limits = [
+            SearchLimits {
+                depth: None,
+                nodes: Some(1),
+                time: None,
+            },
+            SearchLimits {
+                depth: None,
+                nodes: None,
+                time: Some(Duration::ZERO),
+            },
+        ];
+        for limits in limits {
+            let result = search(&game, limits);
+            assert_eq!(result.depth, 1);
+            let best_move = result.best_move.unwrap();
+            assert!(LegalMove::from_move(&game, best_move.to_move()).is_ok());
+        }
+    }
+
+    #[test]
+    fn table_is_shared() {
+        let game = Game::default();
+        let mut searcher = Searcher::new(Heuristic::default());
+        let limits = SearchLimits {
+            depth: Some(1),
+            nodes: None,
+            time: None,
+        };
+
+        let first = searcher.search(&game, limits);
+        let stats = searcher.table().stats();
+        assert!(stats.stores > 0);
+
+        let second = searcher.search(&game, limits);
+        assert_eq!(first.score, second.score);
+        assert_eq!(first.best_move, second.best_move);
+        assert!(searcher.table().stats().hits > stats.hits);
+        assert!(searcher.table().stats().hit_rate() > 0.0);
+    }
+
+    #[test]
+    fn stop_handle() {
+        let mut searcher = Searcher::new(Heuristic::default());
+        searcher.stop_handle().stop();
+
+        let mut iterations = 0;
+        let result =
+            searcher.search_with_progress(&Game::default(), SearchLimits::default(), |_| {
+                iterations += 1
+            });
+        assert_eq!(iterations, 1);
+        assert_eq!(result.depth, 1);
+        assert!(result.best_move.is_some());
+    }
+}
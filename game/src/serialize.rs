@@ -0,0 +1,141 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! Most types serialize the way they are declared. [`BoardCoordinate`]s are written in move
+//! notation (`"d5"`), a [`Move`]'s extra throws are a list, and a [`Game`] is its board and player
+//! to move, with its outcome recomputed when it is deserialized. A [`LegalMove`] serializes like a
+//! [`Move`], but can only be deserialized with a [`LegalMoveSeed`] so that it is checked against
+//! the game it will be played in.
+
+use crate::{Board, BoardCoordinate, Direction, Game, LegalMove, Move, Player};
+use serde::{de::DeserializeSeed, Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for BoardCoordinate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BoardCoordinate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct MoveRepr {
+    player: Player,
+    messenger: BoardCoordinate,
+    direction: Direction,
+    first_throw: Direction,
+    extra_throws: Vec<Direction>,
+}
+
+impl From<Move> for MoveRepr {
+    fn from(move_: Move) -> Self {
+        let mut extra_throws = Vec::new();
+        if let Some((second, rest)) = move_.extra_throws {
+            extra_throws.push(second);
+            if let Some((third, fourth)) = rest {
+                extra_throws.push(third);
+                extra_throws.extend(fourth);
+            }
+        }
+        MoveRepr {
+            player: move_.player,
+            messenger: move_.messenger,
+            direction: move_.direction,
+            first_throw: move_.first_throw,
+            extra_throws,
+        }
+    }
+}
+
+impl TryFrom<MoveRepr> for Move {
+    type Error = String;
+    fn try_from(repr: MoveRepr) -> Result<Self, Self::Error> {
+        let extra_throws = match repr.extra_throws[..] {
+            [] => None,
+            [second] => Some((second, None)),
+            [second, third] => Some((second, Some((third, None)))),
+            [second, third, fourth] => Some((second, Some((third, Some(fourth))))),
+            _ => {
+                return Err(format!(
+                    "{} extra throws given, at most 3 allowed",
+                    repr.extra_throws.len()
+                ))
+            }
+        };
+        Ok(Move {
+            player: repr.player,
+            messenger: repr.messenger,
+            direction: repr.direction,
+            first_throw: repr.first_throw,
+            extra_throws,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct GameRepr {
+    to_move: Player,
+    board: Board,
+}
+
+impl From<GameRepr> for Game {
+    fn from(repr: GameRepr) -> Self {
+        Game::from_position(repr.board, repr.to_move)
+    }
+}
+
+impl Serialize for LegalMove {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_move().serialize(serializer)
+    }
+}
+
+/// Deserializes a [`LegalMove`], rejecting it if it can't be played in the game.
+#[derive(Clone, Copy)]
+pub struct LegalMoveSeed<'game>(pub &'game Game);
+
+impl<'de> DeserializeSeed<'de> for LegalMoveSeed<'_> {
+    type Value = LegalMove;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let move_ = Move::deserialize(deserializer)?;
+        LegalMove::from_move(self.0, move_).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let game = Game::default();
+        let move_ = game.legal_moves()[0];
+
+        let json = serde_json::to_string(&move_).unwrap();
+        assert_eq!(
+            json,
+            r#"{"player":"Beige","messenger":"d5","direction":"W","first_throw":"SW","extra_throws":[]}"#
+        );
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        assert_eq!(
+            LegalMoveSeed(&game).deserialize(&mut deserializer).unwrap(),
+            move_
+        );
+
+        let black_to_move = Game::from_position(crate::STARTING_BOARD, Player::Black);
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        assert!(LegalMoveSeed(&black_to_move)
+            .deserialize(&mut deserializer)
+            .is_err());
+
+        let json = serde_json::to_string(&game).unwrap();
+        let parsed: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.to_position_string(), game.to_position_string());
+
+        assert!(serde_json::from_str::<BoardCoordinate>(r#""h1""#).is_err());
+    }
+}
@@ -0,0 +1,586 @@
+//! Endgame tablebases. Pieces are never taken off the board, so the number of messengers each
+//! player has never changes and all the positions with the same material can be solved on their
+//! own, working back from the ones where the game is over.
+//!
+//! A table indexes every placement of both cylinders, each player's messengers, and which of the
+//! player to move's messengers are stunned. Its size grows quickly with the number of messengers:
+//! one each is about 23 million positions, so only the smallest endgames are practical.
+//!
+//! A tablebase file is the bytes `cotwtb` and a version byte of 1, then the number of tables as a
+//! little-endian `u32`. Each table is the number of beige and then black messengers as a byte
+//! each, the number of entries as a little-endian `u64` and then every entry as a little-endian
+//! `u16`: 0 for a draw, `2d + 1` if the player to move wins in `d` plies and `2d + 2` if they
+//! lose in `d` plies.
+
+use std::io::{Read, Write};
+
+use crate::{Board, Game, Mask, Outcome, Piece, Player, Square};
+
+const MAGIC: &[u8; 7] = b"cotwtb\x01";
+
+static BINOMIAL: [[u64; 50]; 50] = {
+    let mut binomial = [[0; 50]; 50];
+    let mut n = 0;
+    while n < 50 {
+        binomial[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            binomial[n][k] = binomial[n - 1][k - 1] + binomial[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    binomial
+};
+
+/// The position of a set of squares among all the sets of the same size.
+fn rank(mut squares: Mask) -> u64 {
+    let mut rank = 0;
+    let mut i = 1;
+    while squares != 0 {
+        rank += BINOMIAL[squares.trailing_zeros() as usize][i];
+        squares &= squares - 1;
+        i += 1;
+    }
+    rank
+}
+
+fn unrank(mut rank: u64, count: usize) -> Mask {
+    let mut squares = 0;
+    for i in (1..=count).rev() {
+        let mut square = i - 1;
+        while BINOMIAL[square + 1][i] <= rank {
+            square += 1;
+        }
+        rank -= BINOMIAL[square][i];
+        squares |= 1 << square;
+    }
+    squares
+}
+
+/// The result of a position for the player to move, with perfect play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableResult {
+    /// The game ends in this many plies.
+    Win(u16),
+    Loss(u16),
+    Draw,
+}
+
+impl TableResult {
+    fn from_entry(entry: u16) -> TableResult {
+        match entry {
+            0 => TableResult::Draw,
+            entry if entry % 2 == 1 => TableResult::Win((entry - 1) / 2),
+            entry => TableResult::Loss((entry - 2) / 2),
+        }
+    }
+
+    fn to_entry(self) -> u16 {
+        match self {
+            TableResult::Draw => 0,
+            TableResult::Win(distance) => 2 * distance + 1,
+            TableResult::Loss(distance) => 2 * distance + 2,
+        }
+    }
+}
+
+/// Where each position with some material goes in a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    beige: usize,
+    black: usize,
+}
+
+impl Layout {
+    fn messengers(&self, player: Player) -> usize {
+        match player {
+            Player::Beige => self.beige,
+            Player::Black => self.black,
+        }
+    }
+
+    fn side_len(&self, to_move: Player) -> u64 {
+        (49 * 49_u64)
+            .saturating_mul(BINOMIAL[49][self.beige])
+            .saturating_mul(BINOMIAL[49][self.black])
+            .saturating_mul(1 << self.messengers(to_move))
+    }
+
+    fn len(&self) -> u64 {
+        self.side_len(Player::Beige)
+            .saturating_add(self.side_len(Player::Black))
+    }
+
+    fn index(&self, board: &Board, to_move: Player) -> Option<usize> {
+        let cylinders = [Player::Beige, Player::Black].map(|player| {
+            board.pieces(match player {
+                Player::Beige => Square::Beige(Piece::Cylinder),
+                Player::Black => Square::Black(Piece::Cylinder),
+            })
+        });
+        let messengers = [Player::Beige, Player::Black].map(|player| board.messengers(player));
+        let just_moved = match to_move {
+            Player::Beige => Square::Black(Piece::StunnedMessenger),
+            Player::Black => Square::Beige(Piece::StunnedMessenger),
+        };
+        if cylinders
+            .iter()
+            .any(|cylinders| cylinders.count_ones() != 1)
+            || messengers[0].count_ones() as usize != self.beige
+            || messengers[1].count_ones() as usize != self.black
+            || board.pieces(just_moved) != 0
+        {
+            return None;
+        }
+
+        let stunned = board.pieces(match to_move {
+            Player::Beige => Square::Beige(Piece::StunnedMessenger),
+            Player::Black => Square::Black(Piece::StunnedMessenger),
+        });
+        let mut stun_bits = 0;
+        let mut to_move_messengers = board.messengers(to_move);
+        let mut i = 0;
+        while to_move_messengers != 0 {
+            if stunned & (1 << to_move_messengers.trailing_zeros()) != 0 {
+                stun_bits |= 1 << i;
+            }
+            to_move_messengers &= to_move_messengers - 1;
+            i += 1;
+        }
+
+        let mut index = cylinders[0].trailing_zeros() as u64;
+        index = index * 49 + cylinders[1].trailing_zeros() as u64;
+        index = index * BINOMIAL[49][self.beige] + rank(messengers[0]);
+        index = index * BINOMIAL[49][self.black] + rank(messengers[1]);
+        index = (index << self.messengers(to_move)) + stun_bits;
+        if to_move == Player::Black {
+            index += self.side_len(Player::Beige);
+        }
+        Some(index as usize)
+    }
+
+    /// The position at `index`, or `None` if pieces would overlap.
+    fn position(&self, index: usize) -> Option<(Board, Player)> {
+        let mut index = index as u64;
+        let to_move = if index < self.side_len(Player::Beige) {
+            Player::Beige
+        } else {
+            index -= self.side_len(Player::Beige);
+            Player::Black
+        };
+
+        let stun_bits = index & ((1 << self.messengers(to_move)) - 1);
+        index >>= self.messengers(to_move);
+        let black_messengers = unrank(index % BINOMIAL[49][self.black], self.black);
+        index /= BINOMIAL[49][self.black];
+        let beige_messengers = unrank(index % BINOMIAL[49][self.beige], self.beige);
+        index /= BINOMIAL[49][self.beige];
+        let black_cylinder: Mask = 1 << (index % 49);
+        let beige_cylinder: Mask = 1 << (index / 49);
+
+        let pieces = [
+            beige_cylinder,
+            beige_messengers,
+            black_cylinder,
+            black_messengers,
+        ];
+        if pieces.iter().map(|mask| mask.count_ones()).sum::<u32>()
+            != pieces.iter().fold(0, |all, mask| all | mask).count_ones()
+        {
+            return None;
+        }
+
+        let mut stunned = 0;
+        let mut to_move_messengers = match to_move {
+            Player::Beige => beige_messengers,
+            Player::Black => black_messengers,
+        };
+        let mut i = 0;
+        while to_move_messengers != 0 {
+            if stun_bits & (1 << i) != 0 {
+                stunned |= 1 << to_move_messengers.trailing_zeros();
+            }
+            to_move_messengers &= to_move_messengers - 1;
+            i += 1;
+        }
+
+        let mut masks = [0; 6];
+        masks[0] = beige_cylinder;
+        masks[3] = black_cylinder;
+        match to_move {
+            Player::Beige => {
+                masks[1] = beige_messengers & !stunned;
+                masks[2] = stunned;
+                masks[4] = black_messengers;
+            }
+            Player::Black => {
+                masks[1] = beige_messengers;
+                masks[4] = black_messengers & !stunned;
+                masks[5] = stunned;
+            }
+        }
+        Some((Board::from_masks(masks), to_move))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableError {
+    /// The table would have this many positions, more than [`Table::MAX_POSITIONS`].
+    TooManyPositions(u64),
+}
+
+impl std::fmt::Display for TableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::TooManyPositions(positions) => write!(
+                f,
+                "{} positions is too many to solve, at most {} are allowed",
+                positions,
+                Table::MAX_POSITIONS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+/// The solved positions for one amount of material.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    layout: Layout,
+    entries: Vec<u16>,
+}
+
+impl Table {
+    /// The most positions a table can have. Solving takes around a hundred bytes per position, so
+    /// this is enough for one messenger each, or two for one player and none for the other.
+    pub const MAX_POSITIONS: u64 = 1 << 25;
+
+    /// Solve every position where beige has `beige` messengers and black has `black`.
+    pub fn generate(beige: usize, black: usize) -> Result<Table, TableError> {
+        let layout = Layout { beige, black };
+        let len = layout.len();
+        if len > Table::MAX_POSITIONS {
+            return Err(TableError::TooManyPositions(len));
+        }
+        let len = len as usize;
+
+        let mut entries = vec![0; len];
+        let mut undecided = Vec::new();
+        for (index, entry) in entries.iter_mut().enumerate() {
+            let Some((board, to_move)) = layout.position(index) else {
+                continue;
+            };
+            let game = Game::from_position(board, to_move);
+            *entry = match game.outcome() {
+                Some(Outcome::Win(winner, _)) if winner == to_move => TableResult::Win(0),
+                Some(Outcome::Win(..)) => TableResult::Loss(0),
+                Some(Outcome::Draw(_)) => TableResult::Draw,
+                None => {
+                    undecided.push(index as u32);
+                    continue;
+                }
+            }
+            .to_entry();
+        }
+
+        // the positions after each undecided position's moves, `children[ends[i - 1]..ends[i]]`
+        let mut children = Vec::new();
+        let mut ends = Vec::with_capacity(undecided.len());
+        for &index in undecided.iter() {
+            let (board, to_move) = layout.position(index as usize).unwrap();
+            let game = Game::from_position(board, to_move);
+            let mut moves = game
+                .moves(Default::default())
+                .map(|(_, board)| {
+                    layout
+                        .index(&board, to_move.opponent())
+                        .expect("moves keep the same material") as u32
+                })
+                .collect::<Vec<_>>();
+            moves.sort_unstable();
+            moves.dedup();
+            children.extend(moves);
+            ends.push(children.len() as u32);
+        }
+
+        let mut distance = 1;
+        loop {
+            let mut decided = Vec::new();
+            let mut start = 0;
+            for (&index, &end) in undecided.iter().zip(ends.iter()) {
+                let results = children[start..end as usize]
+                    .iter()
+                    .map(|&child| TableResult::from_entry(entries[child as usize]));
+                start = end as usize;
+
+                let mut all_won = true;
+                for result in results {
+                    match result {
+                        TableResult::Loss(d) if d == distance - 1 => {
+                            decided.push((index, TableResult::Win(distance)));
+                            all_won = false;
+                            break;
+                        }
+                        TableResult::Win(d) if d < distance => {}
+                        _ => all_won = false,
+                    }
+                }
+                if all_won {
+                    decided.push((index, TableResult::Loss(distance)));
+                }
+            }
+            if decided.is_empty() {
+                break;
+            }
+
+            for &(index, result) in decided.iter() {
+                entries[index as usize] = result.to_entry();
+            }
+
+            // drop the decided positions
+            let mut kept = 0;
+            let mut kept_children = 0;
+            let mut start = 0;
+            for i in 0..undecided.len() {
+                let end = ends[i] as usize;
+                if entries[undecided[i] as usize] == 0 {
+                    undecided[kept] = undecided[i];
+                    children.copy_within(start..end, kept_children);
+                    kept_children += end - start;
+                    ends[kept] = kept_children as u32;
+                    kept += 1;
+                }
+                start = end;
+            }
+            undecided.truncate(kept);
+            ends.truncate(kept);
+            children.truncate(kept_children);
+            distance += 1;
+        }
+
+        Ok(Table { layout, entries })
+    }
+
+    /// The number of beige and black messengers in every position in the table.
+    pub fn material(&self) -> (usize, usize) {
+        (self.layout.beige, self.layout.black)
+    }
+
+    pub fn probe(&self, game: &Game) -> Option<TableResult> {
+        let index = self.layout.index(game.board(), game.to_move())?;
+        Some(TableResult::from_entry(self.entries[index]))
+    }
+}
+
+/// Tables for several amounts of material.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tablebase {
+    tables: Vec<Table>,
+}
+
+impl Tablebase {
+    /// Solve every position with up to `max_messengers` messengers for each player.
+    pub fn generate(max_messengers: usize) -> Result<Tablebase, TableError> {
+        let mut tablebase = Tablebase::default();
+        for beige in 0..=max_messengers {
+            for black in 0..=max_messengers {
+                tablebase.insert(Table::generate(beige, black)?);
+            }
+        }
+        Ok(tablebase)
+    }
+
+    /// Add a table, replacing any with the same material.
+    pub fn insert(&mut self, table: Table) {
+        self.tables.retain(|old| old.layout != table.layout);
+        self.tables.push(table);
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// The result of `game`, if there's a table for its material.
+    pub fn probe(&self, game: &Game) -> Option<TableResult> {
+        let material = (
+            game.board().messengers(Player::Beige).count_ones() as usize,
+            game.board().messengers(Player::Black).count_ones() as usize,
+        );
+        self.tables
+            .iter()
+            .find(|table| table.material() == material)?
+            .probe(game)
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.tables.len() as u32).to_le_bytes())?;
+        for table in self.tables.iter() {
+            writer.write_all(&[table.layout.beige as u8, table.layout.black as u8])?;
+            writer.write_all(&(table.entries.len() as u64).to_le_bytes())?;
+            let bytes = table
+                .entries
+                .iter()
+                .flat_map(|entry| entry.to_le_bytes())
+                .collect::<Vec<_>>();
+            writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    pub fn read(mut reader: impl Read) -> std::io::Result<Tablebase> {
+        let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+        let mut magic = [0; 7];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a tablebase file"));
+        }
+        let mut count = [0; 4];
+        reader.read_exact(&mut count)?;
+
+        let mut tablebase = Tablebase::default();
+        for _ in 0..u32::from_le_bytes(count) {
+            let mut header = [0; 10];
+            reader.read_exact(&mut header)?;
+            let layout = Layout {
+                beige: header[0] as usize,
+                black: header[1] as usize,
+            };
+            let len = u64::from_le_bytes(header[2..].try_into().unwrap());
+            if layout.beige > 49
+                || layout.black > 49
+                || len != layout.len()
+                || len > u32::MAX as u64
+            {
+                return Err(invalid("table has the wrong number of entries"));
+            }
+
+            let mut bytes = vec![0; 2 * len as usize];
+            reader.read_exact(&mut bytes)?;
+            let entries = bytes
+                .chunks_exact(2)
+                .map(|entry| u16::from_le_bytes([entry[0], entry[1]]))
+                .collect();
+            tablebase.insert(Table { layout, entries });
+        }
+        Ok(tablebase)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{rng::Rng, search::WIN, Heuristic, SearchLimits, Searcher};
+
+    #[test]
+    fn index_round_trip() {
+        let mut rng = Rng::new(22);
+        for layout in [Layout { beige: 1, black: 1 }, Layout { beige: 3, black: 2 }] {
+            let mut positions = 0;
+            while positions < 1000 {
+                let index = rng.below(layout.len() as usize);
+                if let Some((board, to_move)) = layout.position(index) {
+                    assert_eq!(layout.index(&board, to_move), Some(index));
+                    positions += 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn entries() {
+        for result in [
+            TableResult::Draw,
+            TableResult::Win(0),
+            TableResult::Win(17),
+            TableResult::Loss(0),
+            TableResult::Loss(18),
+        ] {
+            assert_eq!(TableResult::from_entry(result.to_entry()), result);
+        }
+        let error = Tablebase::read(b"cotwtb\x02\0\0\0\0".as_slice()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn generate_and_probe() {
+        assert_eq!(
+            Table::generate(1, 2),
+            Err(TableError::TooManyPositions(
+                Layout { beige: 1, black: 2 }.len()
+            ))
+        );
+
+        let table = Table::generate(0, 1).unwrap();
+        // every position's result follows from the results after each of its moves
+        let mut rng = Rng::new(22);
+        let mut positions = 0;
+        while positions < 300 {
+            let index = rng.below(table.entries.len());
+            let Some((board, to_move)) = table.layout.position(index) else {
+                continue;
+            };
+            let game = Game::from_position(board, to_move);
+            if game.is_over() {
+                continue;
+            }
+            let children = game
+                .moves(Default::default())
+                .map(|(_, board)| table.probe(&game.after(board)).unwrap())
+                .collect::<Vec<_>>();
+            let fastest_win = children
+                .iter()
+                .filter_map(|result| match result {
+                    TableResult::Loss(d) => Some(d + 1),
+                    _ => None,
+                })
+                .min();
+            let expected = match fastest_win {
+                Some(d) => TableResult::Win(d),
+                None => match children
+                    .iter()
+                    .map(|result| match result {
+                        TableResult::Win(d) => Some(d + 1),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                {
+                    Some(distances) => TableResult::Loss(distances.into_iter().max().unwrap()),
+                    None => TableResult::Draw,
+                },
+            };
+            assert_eq!(
+                table.probe(&game),
+                Some(expected),
+                "{}",
+                game.to_position_string()
+            );
+            positions += 1;
+        }
+
+        let mut tablebase = Tablebase::default();
+        tablebase.insert(table);
+        let mut file = Vec::new();
+        tablebase.write(&mut file).unwrap();
+        assert_eq!(Tablebase::read(file.as_slice()).unwrap(), tablebase);
+
+        // black wins by moving, since beige can't
+        let game = Game::from_position_string("3C3/7/7/3m3/3c3/7/7 black").unwrap();
+        assert_eq!(tablebase.probe(&game), Some(TableResult::Win(1)));
+        let beige = Game::from_position_string("3C3/7/7/3m3/3c3/7/7 beige").unwrap();
+        assert_eq!(tablebase.probe(&beige), Some(TableResult::Loss(0)));
+        assert_eq!(tablebase.probe(&Game::default()), None);
+
+        let mut searcher = Searcher::new(Heuristic::default());
+        searcher.set_tablebase(std::sync::Arc::new(tablebase));
+        let limits = SearchLimits {
+            depth: Some(1),
+            nodes: None,
+            time: None,
+        };
+        assert_eq!(searcher.search(&game, limits).score, WIN - 1);
+    }
+}
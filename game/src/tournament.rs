@@ -0,0 +1,375 @@
+//! Matches between two engines, for measuring whether a change makes an engine stronger.
+//!
+//! Games are played in pairs from the same opening with colours swapped, so neither engine gets
+//! the better side of an opening more often. Results are from the point of view of the first
+//! engine.
+
+use crate::{Engine, Game, GameHistory, GameRecord, Player, RecordResult};
+
+pub struct Contestant {
+    pub name: String,
+    pub engine: Box<dyn Engine>,
+}
+
+impl Contestant {
+    pub fn new(name: impl Into<String>, engine: impl Engine + 'static) -> Contestant {
+        Contestant {
+            name: name.into(),
+            engine: Box::new(engine),
+        }
+    }
+}
+
+/// The sequential probability ratio test, for stopping a match as soon as it's clear whether the
+/// first engine is `elo0` or `elo1` stronger than the second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// The chance of accepting `elo1` when `elo0` is true.
+    pub alpha: f64,
+    /// The chance of accepting `elo0` when `elo1` is true.
+    pub beta: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hypothesis {
+    Elo0,
+    Elo1,
+}
+
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo_difference(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Sprt {
+        Sprt {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// The log-likelihood ratio is compared against these to decide.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    /// The log of how much more likely `elo1` is than `elo0` given the results so far, using a
+    /// normal approximation to the distribution of game scores.
+    pub fn llr(&self, results: &MatchResult) -> f64 {
+        let games = results.games() as f64;
+        let variance = results.variance();
+        if games == 0.0 || variance == 0.0 {
+            return 0.0;
+        }
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        (score1 - score0) * (2.0 * results.score() - score0 - score1) * games / (2.0 * variance)
+    }
+
+    pub fn decide(&self, results: &MatchResult) -> Option<Hypothesis> {
+        let llr = self.llr(results);
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            Some(Hypothesis::Elo0)
+        } else if llr >= upper {
+            Some(Hypothesis::Elo1)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// Set if the match was stopped early by the SPRT.
+    pub accepted: Option<Hypothesis>,
+}
+
+/// An Elo difference and the distance to either end of its 95% confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elo {
+    pub difference: f64,
+    pub error: f64,
+}
+
+impl std::fmt::Display for Elo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+.1} +/- {:.1}", self.difference, self.error)
+    }
+}
+
+impl MatchResult {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The average points per game, counting a draw as half a win.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    fn variance(&self) -> f64 {
+        let score = self.score();
+        (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / self.games() as f64
+    }
+
+    /// `None` until both engines have scored, since the difference is infinite otherwise.
+    pub fn elo(&self) -> Option<Elo> {
+        let score = self.score();
+        if !(score > 0.0 && score < 1.0) {
+            return None;
+        }
+        let margin = 1.959964 * (self.variance() / self.games() as f64).sqrt();
+        let low = elo_difference((score - margin).max(f64::EPSILON));
+        let high = elo_difference((score + margin).min(1.0 - f64::EPSILON));
+        Some(Elo {
+            difference: elo_difference(score),
+            error: (high - low) / 2.0,
+        })
+    }
+}
+
+impl std::fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins, self.draws, self.losses)?;
+        if let Some(elo) = self.elo() {
+            write!(f, ", elo {}", elo)?;
+        }
+        match self.accepted {
+            Some(Hypothesis::Elo0) => write!(f, ", sprt accepted elo0"),
+            Some(Hypothesis::Elo1) => write!(f, ", sprt accepted elo1"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchConfig {
+    pub games: u32,
+    /// Positions to start games from, each used for a pair of games. Games start from the
+    /// starting position if this is empty.
+    pub openings: Vec<Game>,
+    /// Games still going after this many plies are recorded as unfinished and count as draws.
+    pub max_plies: usize,
+    pub sprt: Option<Sprt>,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            games: 100,
+            openings: Vec::new(),
+            max_plies: 200,
+            sprt: None,
+        }
+    }
+}
+
+pub struct Match {
+    pub config: MatchConfig,
+    pub first: Contestant,
+    pub second: Contestant,
+}
+
+impl Match {
+    pub fn new(config: MatchConfig, first: Contestant, second: Contestant) -> Match {
+        Match {
+            config,
+            first,
+            second,
+        }
+    }
+
+    /// Play the match, calling `on_game` with the record of each game and the results so far.
+    pub fn play(&mut self, mut on_game: impl FnMut(&GameRecord, &MatchResult)) -> MatchResult {
+        let mut results = MatchResult::default();
+        for round in 0..self.config.games {
+            let start = match self.config.openings.len() {
+                0 => Game::default(),
+                n => self.config.openings[(round as usize / 2) % n],
+            };
+            let first_is_beige = round % 2 == 0;
+            let (beige, black) = match first_is_beige {
+                true => (&mut self.first, &mut self.second),
+                false => (&mut self.second, &mut self.first),
+            };
+
+            let mut record = play_game(beige, black, start, self.config.max_plies);
+            record.set_tag("Round", &(round + 1).to_string());
+
+            let first = match first_is_beige {
+                true => Player::Beige,
+                false => Player::Black,
+            };
+            match winner(record.result) {
+                Some(winner) if winner == first => results.wins += 1,
+                Some(_) => results.losses += 1,
+                None => results.draws += 1,
+            }
+            results.accepted = self.config.sprt.and_then(|sprt| sprt.decide(&results));
+
+            on_game(&record, &results);
+            if results.accepted.is_some() {
+                break;
+            }
+        }
+        results
+    }
+}
+
+fn winner(result: RecordResult) -> Option<Player> {
+    match result {
+        RecordResult::BeigeWon => Some(Player::Beige),
+        RecordResult::BlackWon => Some(Player::Black),
+        RecordResult::Draw | RecordResult::Unfinished => None,
+    }
+}
+
+/// Play one game, after telling both engines it's a new game. An engine that doesn't give a move
+/// when it has one loses by forfeit.
+pub fn play_game(
+    beige: &mut Contestant,
+    black: &mut Contestant,
+    start: Game,
+    max_plies: usize,
+) -> GameRecord {
+    beige.engine.new_game();
+    black.engine.new_game();
+    let mut history = GameHistory::new(start);
+    let mut forfeit = None;
+    while !history.game().is_over() && history.len() < max_plies {
+        let game = *history.game();
+        let contestant = match game.to_move() {
+            Player::Beige => &mut *beige,
+            Player::Black => &mut *black,
+        };
+        let made = contestant
+            .engine
+            .choose_move(&game)
+            .map(|move_| history.make_move(&move_));
+        if !matches!(made, Some(Ok(_))) {
+            forfeit = Some(game.to_move());
+            break;
+        }
+    }
+
+    let mut record = GameRecord::from_history(&history);
+    record.set_tag("Beige", &beige.name);
+    record.set_tag("Black", &black.name);
+    if let Some(loser) = forfeit {
+        record.result = match loser {
+            Player::Beige => RecordResult::BlackWon,
+            Player::Black => RecordResult::BeigeWon,
+        };
+        record.set_tag("Result", &record.result.to_string());
+        record.set_tag("Termination", "forfeit");
+    } else if !history.game().is_over() {
+        record.set_tag("Termination", "adjudicated");
+    }
+    record
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AlphaBeta, LegalMove, SearchLimits};
+    use std::{cell::Cell, rc::Rc};
+
+    /// Counts the games it's told about.
+    struct Counting {
+        engine: AlphaBeta<crate::Heuristic>,
+        games: Rc<Cell<u32>>,
+    }
+
+    impl Engine for Counting {
+        fn choose_move(&mut self, game: &Game) -> Option<LegalMove> {
+            self.engine.choose_move(game)
+        }
+
+        fn new_game(&mut self) {
+            self.games.set(self.games.get() + 1);
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let even = MatchResult {
+            wins: 30,
+            draws: 40,
+            losses: 30,
+            accepted: None,
+        };
+        let elo = even.elo().unwrap();
+        assert_eq!(elo.difference, 0.0);
+        assert!(elo.error > 0.0);
+        assert!(Sprt::new(0.0, 10.0).llr(&even) < 0.0);
+
+        let better = MatchResult {
+            wins: 300,
+            draws: 100,
+            losses: 100,
+            accepted: None,
+        };
+        // a score of 0.7
+        assert!((better.elo().unwrap().difference - 147.2).abs() < 0.1);
+        assert_eq!(Sprt::new(0.0, 10.0).decide(&better), Some(Hypothesis::Elo1));
+
+        let sweep = MatchResult {
+            wins: 10,
+            ..Default::default()
+        };
+        assert_eq!(sweep.elo(), None);
+    }
+
+    #[test]
+    fn play_match() {
+        let limits = SearchLimits {
+            depth: Some(1),
+            nodes: None,
+            time: None,
+        };
+        let config = MatchConfig {
+            games: 2,
+            max_plies: 4,
+            ..Default::default()
+        };
+        let games = Rc::new(Cell::new(0));
+        let counting = Counting {
+            engine: AlphaBeta::new(limits),
+            games: games.clone(),
+        };
+        let mut match_ = Match::new(
+            config,
+            Contestant::new("first", counting),
+            Contestant::new("second", AlphaBeta::new(limits)),
+        );
+
+        let mut records = Vec::new();
+        let results = match_.play(|record, _| records.push(record.clone()));
+        assert_eq!(results.games(), 2);
+        assert_eq!(games.get(), 2);
+        assert_eq!(records[0].tag("Beige"), Some("first"));
+        assert_eq!(records[1].tag("Beige"), Some("second"));
+        for record in records {
+            assert_eq!(record.moves.len(), 4);
+            assert_eq!(record.tag("Termination"), Some("adjudicated"));
+            record.replay().unwrap();
+        }
+    }
+}
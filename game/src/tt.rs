@@ -0,0 +1,337 @@
+//! A fixed-size transposition table, remembering what the search found out about positions it
+//! has already visited. Each position hashes to one slot, and a new entry replaces the one in its
+//! slot unless the old entry was searched deeper. The one exception is an exact score replacing a
+//! bound on the same position, which is worth more even from a shallower search.
+//!
+//! The table can be shared between threads without locking. Each entry is packed into one word
+//! and stored next to its key xor that word, so an entry torn by two threads writing the slot at
+//! once doesn't match its key and is ignored.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{search::Score, BoardCoordinate, Direction, LegalMove, Move, Player};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact.
+    Exact,
+    /// The real score is at least this, the search stopped early after finding a good enough
+    /// move.
+    Lower,
+    /// The real score is at most this, no move reached alpha.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Entry {
+    pub key: u64,
+    pub depth: u32,
+    pub score: Score,
+    pub bound: Bound,
+    pub best_move: Option<LegalMove>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    /// Stores which replaced an entry for a different position.
+    pub overwrites: u64,
+    /// Stores which were dropped because the slot held a deeper entry.
+    pub rejected: u64,
+}
+
+impl TableStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.probes {
+            0 => 0.0,
+            probes => self.hits as f64 / probes as f64,
+        }
+    }
+}
+
+/// In the order of their discriminants.
+const DIRECTIONS: [Direction; 16] = {
+    use Direction::*;
+    [
+        NW2, N2, NE2, NW, N, NE, W2, W, E, E2, SW, S, SE, SW2, S2, SE2,
+    ]
+};
+
+const SCORE_BITS: u32 = 22;
+const SCORE_OFFSET: i64 = 1 << (SCORE_BITS - 1);
+const MOVE_BITS: u32 = 30;
+
+/// From the lowest bit: 30 bits of move, 22 bits of score, 2 bits of bound, 8 bits of depth, and
+/// a bit set in every packed entry so that zero means an empty slot.
+fn pack(entry: &Entry) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    let score = (entry.score as i64 + SCORE_OFFSET) as u64 & ((1 << SCORE_BITS) - 1);
+    entry.best_move.map_or(0, pack_move)
+        | score << MOVE_BITS
+        | bound << (MOVE_BITS + SCORE_BITS)
+        | (entry.depth.min(255) as u64) << (MOVE_BITS + SCORE_BITS + 2)
+        | 1 << 63
+}
+
+fn unpack(key: u64, data: u64) -> Entry {
+    let score = (data >> MOVE_BITS) & ((1 << SCORE_BITS) - 1);
+    Entry {
+        key,
+        depth: ((data >> (MOVE_BITS + SCORE_BITS + 2)) & 0xff) as u32,
+        score: (score as i64 - SCORE_OFFSET) as Score,
+        bound: match (data >> (MOVE_BITS + SCORE_BITS)) & 0b11 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        },
+        best_move: unpack_move(data & ((1 << MOVE_BITS) - 1)),
+    }
+}
+
+/// From the lowest bit: 4 bits for each of up to three extra throws, 2 bits for how many there
+/// are, 4 bits each for the first throw and the direction, 6 bits for the messenger, 1 bit for
+/// the player and a bit set for every move.
+fn pack_move(move_: LegalMove) -> u64 {
+    let move_ = move_.to_move();
+    let (extra, count) = match move_.extra_throws {
+        None => (0, 0),
+        Some((second, None)) => (second as u64, 1),
+        Some((second, Some((third, None)))) => (second as u64 | (third as u64) << 4, 2),
+        Some((second, Some((third, Some(fourth))))) => (
+            second as u64 | (third as u64) << 4 | (fourth as u64) << 8,
+            3,
+        ),
+    };
+    extra
+        | count << 12
+        | (move_.first_throw as u64) << 14
+        | (move_.direction as u64) << 18
+        | (move_.messenger.index() as u64) << 22
+        | ((move_.player == Player::Black) as u64) << 28
+        | 1 << 29
+}
+
+fn unpack_move(bits: u64) -> Option<LegalMove> {
+    if bits & 1 << 29 == 0 {
+        return None;
+    }
+    let direction = |shift: u32| DIRECTIONS[(bits >> shift) as usize & 0xf];
+    let extra_throws = match (bits >> 12) & 0b11 {
+        0 => None,
+        1 => Some((direction(0), None)),
+        2 => Some((direction(0), Some((direction(4), None)))),
+        _ => Some((direction(0), Some((direction(4), Some(direction(8)))))),
+    };
+    Some(LegalMove(Move {
+        player: match (bits >> 28) & 1 {
+            0 => Player::Beige,
+            _ => Player::Black,
+        },
+        messenger: BoardCoordinate::from_index((bits >> 22) as usize & 0x3f),
+        direction: direction(18),
+        first_throw: direction(14),
+        extra_throws,
+    }))
+}
+
+#[derive(Default)]
+struct Slot {
+    /// The entry's key xor `data`.
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+#[derive(Default)]
+struct AtomicStats {
+    probes: AtomicU64,
+    hits: AtomicU64,
+    stores: AtomicU64,
+    overwrites: AtomicU64,
+    rejected: AtomicU64,
+}
+
+fn count(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    stats: AtomicStats,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_SIZE: usize = 1 << 16;
+
+    /// A table with room for `size` entries, rounded up to a power of two.
+    pub fn new(size: usize) -> TranspositionTable {
+        TranspositionTable {
+            slots: (0..size.max(1).next_power_of_two())
+                .map(|_| Slot::default())
+                .collect(),
+            stats: AtomicStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        &self.slots[key as usize & (self.slots.len() - 1)]
+    }
+
+    /// The entry in `slot`, if it wasn't torn.
+    fn load(slot: &Slot) -> Option<Entry> {
+        let data = slot.data.load(Ordering::Relaxed);
+        let key = slot.check.load(Ordering::Relaxed) ^ data;
+        (data != 0).then(|| unpack(key, data))
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        count(&self.stats.probes);
+        let entry = Self::load(self.slot(key)).filter(|entry| entry.key == key);
+        if entry.is_some() {
+            count(&self.stats.hits);
+        }
+        entry
+    }
+
+    pub fn store(&self, entry: Entry) {
+        let slot = self.slot(entry.key);
+        match Self::load(slot) {
+            Some(old)
+                if old.depth > entry.depth
+                    && (old.key != entry.key
+                        || old.bound == Bound::Exact
+                        || entry.bound != Bound::Exact) =>
+            {
+                count(&self.stats.rejected);
+                return;
+            }
+            Some(old) if old.key != entry.key => count(&self.stats.overwrites),
+            _ => {}
+        }
+        count(&self.stats.stores);
+        let data = pack(&entry);
+        slot.check.store(entry.key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> TableStats {
+        let stats = &self.stats;
+        TableStats {
+            probes: stats.probes.load(Ordering::Relaxed),
+            hits: stats.hits.load(Ordering::Relaxed),
+            stores: stats.stores.load(Ordering::Relaxed),
+            overwrites: stats.overwrites.load(Ordering::Relaxed),
+            rejected: stats.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        let stats = &self.stats;
+        for counter in [
+            &stats.probes,
+            &stats.hits,
+            &stats.stores,
+            &stats.overwrites,
+            &stats.rejected,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.check.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.reset_stats();
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(TranspositionTable::DEFAULT_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replace_by_depth() {
+        let table = TranspositionTable::new(3);
+        assert_eq!(table.len(), 4);
+
+        let entry = |key, depth, bound| Entry {
+            key,
+            depth,
+            score: depth as Score,
+            bound,
+            best_move: None,
+        };
+        table.store(entry(1, 3, Bound::Exact));
+        table.store(entry(5, 2, Bound::Exact));
+        assert_eq!(table.probe(1).unwrap().depth, 3);
+        assert!(table.probe(5).is_none());
+
+        table.store(entry(5, 4, Bound::Lower));
+        assert_eq!(table.probe(5).unwrap().depth, 4);
+        // a shallower bound on the same position doesn't replace it, but an exact score does
+        table.store(entry(5, 1, Bound::Upper));
+        assert_eq!(table.probe(5).unwrap().bound, Bound::Lower);
+        table.store(entry(5, 2, Bound::Exact));
+        assert_eq!(table.probe(5).unwrap().depth, 2);
+        table.store(entry(5, 1, Bound::Exact));
+        assert_eq!(table.probe(5).unwrap().depth, 2);
+        table.store(entry(5, 2, Bound::Upper));
+        assert_eq!(table.probe(5).unwrap().bound, Bound::Upper);
+
+        assert_eq!(
+            table.stats(),
+            TableStats {
+                probes: 7,
+                hits: 6,
+                stores: 4,
+                overwrites: 1,
+                rejected: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn packing() {
+        let game = crate::Game::from_position(crate::BAD_THROW_2, Player::Black);
+        let moves = game.legal_moves();
+        assert!(moves
+            .iter()
+            .any(|move_| move_.to_move().extra_throws.is_some()));
+
+        for (i, move_) in moves.into_iter().enumerate() {
+            let entry = Entry {
+                key: i as u64,
+                depth: i as u32 % 300,
+                score: [0, -1, 1234, -crate::search::WIN, crate::search::WIN + 1][i % 5],
+                bound: [Bound::Exact, Bound::Lower, Bound::Upper][i % 3],
+                best_move: Some(move_),
+            };
+            let unpacked = unpack(entry.key, pack(&entry));
+            assert_eq!(unpacked.depth, entry.depth.min(255));
+            assert_eq!(unpacked.score, entry.score);
+            assert_eq!(unpacked.bound, entry.bound);
+            assert_eq!(unpacked.best_move, entry.best_move);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Zobrist keys. Every kind of piece on every square has a random key, and a position's hash is
+//! the xor of the keys of its pieces, plus [`BLACK_TO_MOVE`] if it's black's turn. [`Board`]
+//! keeps its hash up to date as pieces are moved, stunned and un-stunned.
+//!
+//! [`Board`]: crate::Board
+
+use crate::{piece_index, rng::splitmix, Square};
+
+/// Indexed by the square's bit in a [`Board`] mask, then the kind of piece.
+///
+/// [`Board`]: crate::Board
+const KEYS: [[u64; 6]; 49] = {
+    let mut keys = [[0; 6]; 49];
+    let mut state = 0x636f_7477;
+    let mut index = 0;
+    while index < 49 {
+        let mut piece = 0;
+        while piece < 6 {
+            let (next, key) = splitmix(state);
+            state = next;
+            keys[index][piece] = key;
+            piece += 1;
+        }
+        index += 1;
+    }
+    keys
+};
+
+pub const BLACK_TO_MOVE: u64 = splitmix(0x0062_6c61_636b).1;
+
+/// The key for `square` at `index`, 0 if it's empty.
+pub(crate) const fn square_key(index: usize, square: Square) -> u64 {
+    match piece_index(square) {
+        Some(piece) => KEYS[index][piece],
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Game, Player};
+
+    #[test]
+    fn incremental() {
+        let mut game = Game::default();
+        for _ in 0..6 {
+            let moves = game.legal_moves();
+            let move_ = moves[moves.len() / 2];
+            game.make_move(&move_).unwrap();
+
+            let board = *game.board();
+            assert_eq!(
+                board.zobrist(),
+                crate::Board::from_squares(board.squares()).zobrist()
+            );
+            assert_eq!(
+                Game::from_position_string(&game.to_position_string())
+                    .unwrap()
+                    .zobrist(),
+                game.zobrist()
+            );
+        }
+
+        let beige = Game::from_position(crate::STARTING_BOARD, Player::Beige);
+        let black = Game::from_position(crate::STARTING_BOARD, Player::Black);
+        assert_eq!(beige.board(), black.board());
+        assert_ne!(beige.zobrist(), black.zobrist());
+    }
+}
@@ -0,0 +1,86 @@
+use cotw::{
+    protocol::{Command, Response},
+    Engine, EngineProcess, Game, SearchLimits,
+};
+
+#[test]
+fn play_against_subprocess() {
+    let mut engine = EngineProcess::spawn(&mut std::process::Command::new(env!(
+        "CARGO_BIN_EXE_cotw-engine"
+    )))
+    .unwrap();
+    assert!(engine.name().starts_with("cotw"));
+    engine.is_ready().unwrap();
+
+    let game = Game::default();
+    let result = engine
+        .go(SearchLimits {
+            depth: Some(1),
+            nodes: None,
+            time: None,
+        })
+        .unwrap();
+    let info = result.info.unwrap();
+    assert_eq!(info.depth, 1);
+    assert_eq!(info.pv.first(), result.best_move.as_ref());
+
+    assert_eq!(engine.limits.depth, Some(3));
+    engine.limits.depth = Some(1);
+    let move_ = engine.choose_move(&game).unwrap();
+    assert_eq!(Some(move_.to_move().into()), result.best_move);
+
+    // stopping while idle does nothing
+    engine.send(&Command::Stop).unwrap();
+    engine.is_ready().unwrap();
+}
+
+#[test]
+fn commands_end_an_infinite_search() {
+    let mut engine = EngineProcess::spawn(&mut std::process::Command::new(env!(
+        "CARGO_BIN_EXE_cotw-engine"
+    )))
+    .unwrap();
+
+    for command in [
+        Command::NewGame,
+        Command::Position {
+            start: None,
+            moves: Vec::new(),
+        },
+        Command::Go(SearchLimits::default()),
+    ] {
+        engine.send(&Command::Go(SearchLimits::default())).unwrap();
+        engine.send(&command).unwrap();
+        while !matches!(engine.recv().unwrap(), Response::BestMove(Some(_))) {}
+    }
+    // the last infinite search is still going
+    engine.stop().unwrap();
+    while !matches!(engine.recv().unwrap(), Response::BestMove(Some(_))) {}
+    engine.is_ready().unwrap();
+}
+
+#[test]
+fn illegal_position_is_not_searched() {
+    let mut engine = EngineProcess::spawn(&mut std::process::Command::new(env!(
+        "CARGO_BIN_EXE_cotw-engine"
+    )))
+    .unwrap();
+    let limits = SearchLimits {
+        depth: Some(1),
+        nodes: None,
+        time: None,
+    };
+
+    // black's messenger, on beige's turn
+    engine
+        .send(&Command::Position {
+            start: None,
+            moves: vec!["d2-N/S".parse().unwrap()],
+        })
+        .unwrap();
+    assert!(matches!(engine.recv().unwrap(), Response::Message(_)));
+    assert_eq!(engine.go(limits).unwrap().best_move, None);
+
+    engine.set_position(&Game::default()).unwrap();
+    assert!(engine.go(limits).unwrap().best_move.is_some());
+}
@@ -0,0 +1,124 @@
+//! JavaScript bindings for the rules engine. Build with
+//! `wasm-pack build --target web`, which puts the package in `pkg`.
+
+use cotw::{BoardCoordinate, LegalMoveSeed, Move};
+use serde::de::DeserializeSeed;
+use wasm_bindgen::{prelude::*, JsCast};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &str = r#"
+export type Player = "Beige" | "Black";
+export type Piece = "Cylinder" | "Messenger" | "StunnedMessenger";
+export type Square = "Empty" | { Beige: Piece } | { Black: Piece };
+export type Board = Square[][];
+export type Coordinate = string;
+export type Direction =
+    | "NW2" | "N2" | "NE2"
+    | "NW" | "N" | "NE"
+    | "W2" | "W" | "E" | "E2"
+    | "SW" | "S" | "SE"
+    | "SW2" | "S2" | "SE2";
+export interface Move {
+    player: Player;
+    messenger: Coordinate;
+    direction: Direction;
+    first_throw: Direction;
+    extra_throws: Direction[];
+}
+export type Reason = "CylinderReachedGoal" | "NoLegalMoves";
+export type Outcome = { Win: [Player, Reason] } | { Draw: Reason };
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Player")]
+    pub type JsPlayer;
+    #[wasm_bindgen(typescript_type = "Board")]
+    pub type JsBoard;
+    #[wasm_bindgen(typescript_type = "Move")]
+    pub type JsMove;
+    #[wasm_bindgen(typescript_type = "Move[]")]
+    pub type JsMoves;
+    #[wasm_bindgen(typescript_type = "Outcome | undefined")]
+    pub type JsOutcome;
+}
+
+fn to_js<T: serde::Serialize, J: JsCast>(value: &T) -> Result<J, JsError> {
+    Ok(serde_wasm_bindgen::to_value(value)?.unchecked_into())
+}
+
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame {
+    game: cotw::Game,
+}
+
+impl Default for WasmGame {
+    fn default() -> Self {
+        WasmGame::new()
+    }
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmGame {
+        WasmGame {
+            game: cotw::Game::default(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = fromPosition)]
+    pub fn from_position(position: &str) -> Result<WasmGame, JsError> {
+        Ok(WasmGame {
+            game: cotw::Game::from_position_string(position)?,
+        })
+    }
+
+    #[wasm_bindgen(js_name = toPosition)]
+    pub fn to_position(&self) -> String {
+        self.game.to_position_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = toMove)]
+    pub fn to_move(&self) -> Result<JsPlayer, JsError> {
+        to_js(&self.game.to_move())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn board(&self) -> Result<JsBoard, JsError> {
+        to_js(self.game.board())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn outcome(&self) -> Result<JsOutcome, JsError> {
+        to_js(&self.game.outcome())
+    }
+
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Result<JsMoves, JsError> {
+        to_js(&self.game.legal_moves())
+    }
+
+    #[wasm_bindgen(js_name = legalMovesFor)]
+    pub fn legal_moves_for(&self, messenger: &str) -> Result<JsMoves, JsError> {
+        let messenger = messenger.parse::<BoardCoordinate>()?;
+        to_js(&self.game.legal_moves_for(messenger))
+    }
+
+    /// The board after `move_`, without playing it. Throws with the reason if the move is
+    /// illegal.
+    #[wasm_bindgen(js_name = attemptMove)]
+    pub fn attempt_move(&self, move_: JsMove) -> Result<JsBoard, JsError> {
+        let move_: Move = serde_wasm_bindgen::from_value(move_.into())?;
+        to_js(&self.game.attempt_move(&move_)?)
+    }
+
+    /// Play `move_`, returning the outcome of the game if it has ended. Throws with the reason if
+    /// the move is illegal.
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, move_: JsMove) -> Result<JsOutcome, JsError> {
+        let deserializer = serde_wasm_bindgen::Deserializer::from(JsValue::from(move_));
+        let move_ = LegalMoveSeed(&self.game).deserialize(deserializer)?;
+        to_js(&self.game.make_move(&move_)?)
+    }
+}